@@ -0,0 +1,152 @@
+// Times force simulation steps on large graphs. Run with
+//   cargo bench --bench force_step
+//   cargo bench --bench force_step --features parallel
+// and compare the two to see what the data-parallel passes buy.
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use gpug::generators::utils::rand_f32;
+use gpug::*;
+
+const SIZES: [usize; 2] = [10_000, 50_000];
+const WARMUP_STEPS: usize = 3;
+const MEASURED_STEPS: usize = 20;
+
+fn main() {
+    println!(
+        "parallel feature: {}",
+        if cfg!(feature = "parallel") {
+            "on"
+        } else {
+            "off"
+        }
+    );
+    for n in SIZES {
+        let positions = scatter(n);
+        let edges = generate_watts_strogatz_graph(n, 3, 0.05);
+        println!("\n{n} nodes, {} edges", edges.len());
+
+        let config = ForceConfig::default();
+        let (xs, ys): (Vec<f32>, Vec<f32>) = positions.iter().copied().unzip();
+        let mut fx = vec![0.0f32; n];
+        let mut fy = vec![0.0f32; n];
+        let legacy = time(|| {
+            hashmap_grid_repulsion(
+                &xs,
+                &ys,
+                &mut fx,
+                &mut fy,
+                config.repulsion,
+                config.cell_size,
+            )
+        });
+        report("grid repulsion, HashMap bins (before)", legacy, None);
+
+        let radii = vec![0.0f32; n];
+        let pinned = vec![false; n];
+        let cx = ForceContext {
+            xs: &xs,
+            ys: &ys,
+            edges: &edges,
+            radii: &radii,
+            pinned: &pinned,
+            alpha: 1.0,
+            config: &config,
+        };
+        let mut repulsion = RepulsionForce::default();
+        let binned = time(|| repulsion.apply(&cx, &mut fx, &mut fy));
+        report("grid repulsion, sorted bins", binned, Some(legacy));
+
+        let mut link = LinkForce::default();
+        link.initialize(&cx);
+        let links = time(|| link.apply(&cx, &mut fx, &mut fy));
+        report("link attraction", links, None);
+
+        for strategy in [RepulsionStrategy::Grid, RepulsionStrategy::BarnesHut] {
+            let config = ForceConfig {
+                repulsion_strategy: strategy,
+                ..ForceConfig::default()
+            };
+            let mut simulation =
+                ForceSimulation::with_config(positions.clone(), edges.clone(), config);
+            simulation.set_radii(&vec![6.0; n]);
+            let step = time(|| simulation.step());
+            report(&format!("full step, {strategy:?}"), step, None);
+        }
+    }
+}
+
+// Random positions at roughly the density the simulation settles to
+fn scatter(n: usize) -> Vec<(f32, f32)> {
+    let mut seed = 0x0BE7_C4A5_E5EE_D000;
+    let side = (n as f32).sqrt() * 40.0;
+    (0..n)
+        .map(|_| (rand_f32(&mut seed) * side, rand_f32(&mut seed) * side))
+        .collect()
+}
+
+// Mean time per call after a few warm-up calls
+fn time(mut f: impl FnMut()) -> Duration {
+    for _ in 0..WARMUP_STEPS {
+        f();
+    }
+    let started = Instant::now();
+    for _ in 0..MEASURED_STEPS {
+        f();
+    }
+    started.elapsed() / MEASURED_STEPS as u32
+}
+
+fn report(label: &str, elapsed: Duration, baseline: Option<Duration>) {
+    let ms = elapsed.as_secs_f64() * 1e3;
+    match baseline {
+        Some(baseline) => println!(
+            "  {label:<40} {ms:>9.3} ms  ({:.2}x)",
+            baseline.as_secs_f64() / elapsed.as_secs_f64()
+        ),
+        None => println!("  {label:<40} {ms:>9.3} ms"),
+    }
+}
+
+// The grid repulsion as it was before the sorted bins, rebuilding a map of
+// cell vectors on every call; kept as the reference point
+fn hashmap_grid_repulsion(
+    xs: &[f32],
+    ys: &[f32],
+    fx: &mut [f32],
+    fy: &mut [f32],
+    repulsion: f32,
+    cell: f32,
+) {
+    let n = xs.len();
+    let cell = cell.max(1.0);
+    let mut bins: HashMap<(i32, i32), Vec<usize>> = HashMap::with_capacity(n * 2);
+    for i in 0..n {
+        let gx = (xs[i] / cell).floor() as i32;
+        let gy = (ys[i] / cell).floor() as i32;
+        bins.entry((gx, gy)).or_default().push(i);
+    }
+    for i in 0..n {
+        let gx = (xs[i] / cell).floor() as i32;
+        let gy = (ys[i] / cell).floor() as i32;
+        for dyg in -1..=1 {
+            for dxg in -1..=1 {
+                let Some(v) = bins.get(&(gx + dxg, gy + dyg)) else {
+                    continue;
+                };
+                for &j in v {
+                    if j <= i {
+                        continue;
+                    }
+                    let dx = xs[j] - xs[i];
+                    let dy = ys[j] - ys[i];
+                    let inv = 1.0 / (dx * dx + dy * dy + 0.01);
+                    fx[i] -= repulsion * dx * inv;
+                    fy[i] -= repulsion * dy * inv;
+                    fx[j] += repulsion * dx * inv;
+                    fy[j] += repulsion * dy * inv;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::{Duration, Instant};
+
+// Shape of a transition's progress over time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    // Maps linear progress in [0, 1] to eased progress (cubic curves)
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) * 0.5
+                }
+            }
+        }
+    }
+}
+
+// How programmatic moves (layouts) are animated; a zero duration moves nodes
+// instantly
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransitionConfig {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(400),
+            easing: Easing::EaseInOut,
+        }
+    }
+}
+
+// Tween of every node from one set of positions to another
+#[derive(Clone, Debug)]
+pub struct PositionTransition {
+    from: Vec<(f32, f32)>,
+    to: Vec<(f32, f32)>,
+    started: Instant,
+    config: TransitionConfig,
+}
+
+impl PositionTransition {
+    pub fn new(from: Vec<(f32, f32)>, to: Vec<(f32, f32)>, config: TransitionConfig) -> Self {
+        Self {
+            from,
+            to,
+            started: Instant::now(),
+            config,
+        }
+    }
+
+    pub fn target(&self) -> &[(f32, f32)] {
+        &self.to
+    }
+
+    // Eased progress in [0, 1] at `now`
+    pub fn progress(&self, now: Instant) -> f32 {
+        if self.config.duration.is_zero() {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        let t = elapsed.as_secs_f32() / self.config.duration.as_secs_f32();
+        self.config.easing.apply(t)
+    }
+
+    pub fn is_finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.config.duration
+    }
+
+    pub fn positions_at(&self, now: Instant) -> Vec<(f32, f32)> {
+        let t = self.progress(now);
+        self.from
+            .iter()
+            .zip(&self.to)
+            .map(|(&(x0, y0), &(x1, y1))| (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASINGS: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    fn transition(easing: Easing) -> PositionTransition {
+        let config = TransitionConfig {
+            duration: Duration::from_millis(400),
+            easing,
+        };
+        PositionTransition::new(
+            vec![(0.0, 0.0), (10.0, -5.0)],
+            vec![(100.0, 50.0), (10.0, 5.0)],
+            config,
+        )
+    }
+
+    #[test]
+    fn transitions_start_at_the_origin_and_end_at_the_target() {
+        for easing in EASINGS {
+            let transition = transition(easing);
+            let start = transition.started;
+            assert_eq!(transition.positions_at(start), transition.from);
+            let end = start + transition.config.duration;
+            assert_eq!(transition.positions_at(end), transition.target());
+            assert_eq!(
+                transition.positions_at(end + Duration::from_secs(1)),
+                transition.target()
+            );
+        }
+    }
+
+    #[test]
+    fn easing_is_monotone() {
+        for easing in EASINGS {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            let mut previous = 0.0;
+            for step in 1..=100 {
+                let value = easing.apply(step as f32 / 100.0);
+                assert!(value >= previous, "{easing:?} falls at step {step}");
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_finish_after_their_duration() {
+        let transition = transition(Easing::EaseInOut);
+        let start = transition.started;
+        assert!(!transition.is_finished(start));
+        assert!(!transition.is_finished(start + Duration::from_millis(200)));
+        assert!(transition.is_finished(start + Duration::from_millis(400)));
+
+        let instant = PositionTransition::new(
+            vec![(0.0, 0.0)],
+            vec![(1.0, 1.0)],
+            TransitionConfig {
+                duration: Duration::ZERO,
+                easing: Easing::Linear,
+            },
+        );
+        assert!(instant.is_finished(instant.started));
+        assert_eq!(instant.positions_at(instant.started), vec![(1.0, 1.0)]);
+    }
+}
@@ -1,11 +1,39 @@
+// How edges are drawn between node centres
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeRouting {
+    Straight,
+    // Horizontal and vertical runs steered around other nodes, see
+    // `orthogonal_routes`
+    Orthogonal,
+}
+
 #[derive(Clone)]
 pub struct GpugEdge {
     pub source: usize,
     pub target: usize,
+    // Rest length of the spring; falls back to `ForceConfig::link_distance`
+    pub length: Option<f32>,
+    // Spring stiffness multiplier; defaults to 1 / min(degree(source), degree(target))
+    pub strength: Option<f32>,
 }
 
 impl GpugEdge {
     pub fn new(source: usize, target: usize) -> Self {
-        Self { source, target }
+        Self {
+            source,
+            target,
+            length: None,
+            strength: None,
+        }
+    }
+
+    pub fn with_length(mut self, length: f32) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = Some(strength);
+        self
     }
 }
@@ -1,5 +1,3 @@
-use gpui::*;
-
 use crate::node::GpugNode;
 
 // Simple xorshift-based PRNG to avoid external dependencies
@@ -37,17 +35,11 @@ pub fn generate_nodes(n: usize) -> Vec<GpugNode> {
     for i in 0..n {
         let rx = rand_f32(&mut seed);
         let ry = rand_f32(&mut seed);
-        let x = px(left + rx * width);
-        let y = px(top + ry * height);
-        nodes.push(GpugNode {
-            id: (i as u64) + 1,
-            x,
-            y,
-            drag_offset: None,
-            zoom: 1.0,
-            pan: point(px(0.0), px(0.0)),
-            selected: false,
-        });
+        nodes.push(GpugNode::new(
+            (i as u64) + 1,
+            left + rx * width,
+            top + ry * height,
+        ));
     }
     nodes
 }
@@ -1,7 +1,43 @@
+pub use crate::animation::{Easing, PositionTransition, TransitionConfig};
+pub use crate::edge::EdgeRouting;
 pub use crate::generators::utils::generate_nodes;
 pub use crate::generators::watts_strogatz::generate_watts_strogatz_graph;
-pub use crate::graph::Graph;
+pub use crate::graph::{Graph, GraphEvent};
+pub use crate::layout::circular::{circular_layout, CircularConfig, CircularLayout, CircularOrder};
+pub use crate::layout::fruchterman_reingold::{
+    fruchterman_reingold, FruchtermanReingoldConfig, FruchtermanReingoldLayout,
+};
+pub use crate::layout::geographic::{
+    coastline, geographic_layout, parse_coordinates, GeographicConfig, GeographicLayout, MapFrame,
+    Projection,
+};
+pub use crate::layout::kamada_kawai::{kamada_kawai, KamadaKawaiConfig, KamadaKawaiLayout};
+pub use crate::layout::layered::{
+    layered_layout, CrossingHeuristic, LayerDirection, LayeredConfig, LayeredLayout,
+};
+pub use crate::layout::multilevel::{multilevel_layout, MultilevelConfig, MultilevelLayout};
+pub use crate::layout::multipartite::{
+    detect_partition, multipartite_layout, MultipartiteConfig, MultipartiteLayout, Partition,
+};
+pub use crate::layout::orthogonal::{
+    orthogonal_layout, orthogonal_routes, OrthogonalConfig, OrthogonalLayout,
+};
+pub use crate::layout::shell::{shell_layout, ShellConfig, ShellGrouping, ShellLayout};
+pub use crate::layout::spectral::{spectral_layout, SpectralConfig, SpectralLayout};
+pub use crate::layout::stress::{stress_layout, StressConfig, StressLayout};
+pub use crate::layout::tree::{tree_layout, TreeConfig, TreeLayout, TreeStyle};
+pub use crate::layout::{attribute_ranks, layout_nodes, snap_to_grid, Layout, LayoutInput};
+pub use crate::simulation::config::{CenteringMode, ForceConfig, ForceModel, RepulsionStrategy};
+pub use crate::simulation::constraints::{project_constraints, Axis, Constraint};
+pub use crate::simulation::force_atlas2::ForceAtlas2Config;
+pub use crate::simulation::force_simulation::ForceSimulation;
+pub use crate::simulation::forces::{Force, ForceContext, GravityForce, LinkForce, RepulsionForce};
+pub use crate::simulation::shared::{PositionSnapshot, SharedSimulation};
+pub use crate::simulation::spatial::SpatialGrid;
+pub mod animation;
 pub mod edge;
 pub mod generators;
 pub mod graph;
+pub mod layout;
 pub mod node;
+pub mod simulation;
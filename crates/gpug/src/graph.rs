@@ -1,9 +1,65 @@
+use std::time::{Duration, Instant};
+
 use gpui::*;
 use gpui::{canvas, div, Context, IntoElement, ParentElement, Render, Styled, Window};
 
-use crate::edge::GpugEdge;
+use crate::animation::{PositionTransition, TransitionConfig};
+use crate::edge::{EdgeRouting, GpugEdge};
 use crate::generators::watts_strogatz::generate_watts_strogatz_graph;
-use crate::node::GpugNode;
+use crate::layout::circular::{CircularConfig, CircularLayout};
+use crate::layout::fruchterman_reingold::FruchtermanReingoldLayout;
+use crate::layout::geographic::{
+    coastline, parse_coordinates, GeographicConfig, GeographicLayout, MapFrame, Projection,
+};
+use crate::layout::kamada_kawai::KamadaKawaiLayout;
+use crate::layout::layered::{LayerDirection, LayeredConfig, LayeredLayout};
+use crate::layout::multilevel::MultilevelLayout;
+use crate::layout::multipartite::{MultipartiteConfig, MultipartiteLayout, Partition};
+use crate::layout::orthogonal::{orthogonal_routes, OrthogonalConfig, OrthogonalLayout};
+use crate::layout::shell::ShellLayout;
+use crate::layout::spectral::SpectralLayout;
+use crate::layout::stress::{StressConfig, StressLayout};
+use crate::layout::tree::{TreeConfig, TreeLayout, TreeStyle};
+use crate::layout::{centroid, recenter_on_grid, Layout, LayoutInput};
+use crate::node::{GpugNode, DEFAULT_NODE_RADIUS};
+use crate::simulation::config::{CenteringMode, ForceConfig, ForceModel, RepulsionStrategy};
+use crate::simulation::constraints::{Axis, Constraint};
+use crate::simulation::force_simulation::ForceSimulation;
+use crate::simulation::forces::Force;
+use crate::simulation::shared::SharedSimulation;
+
+// Temperature the layout is held at while a node is being dragged
+const DRAG_ALPHA: f32 = 0.3;
+// Pause between background physics steps so the layout does not monopolise a worker
+const STEP_INTERVAL: Duration = Duration::from_millis(4);
+// Pivots used by the "sparse stress" layout button
+const SPARSE_STRESS_PIVOTS: usize = 64;
+// Node attribute the "multipartite" button splits parts by when any node has it
+const PARTITION_ATTRIBUTE: &str = "partition";
+// Node attributes the geographic layout reads locations from, in degrees
+const LATITUDE_ATTRIBUTE: &str = "lat";
+const LONGITUDE_ATTRIBUTE: &str = "lon";
+// Minimum distance between centres kept by the "order" constraint buttons
+const ORDER_GAP: f32 = 50.0;
+// Room around the selection left by the "keep in box" button
+const BOX_MARGIN: f32 = 20.0;
+
+// Events emitted by `Graph` for the host application to subscribe to
+#[derive(Clone, Debug)]
+pub enum GraphEvent {
+    // The simulation converged and `playing` was switched off
+    LayoutConverged { sim_tick: u64 },
+}
+
+impl EventEmitter<GraphEvent> for Graph {}
+
+// Orthogonal edge routes and the nodes and edges they were computed for
+struct RouteCache {
+    centres: Vec<(f32, f32)>,
+    radii: Vec<f32>,
+    endpoints: Vec<(usize, usize)>,
+    routes: Vec<Vec<(f32, f32)>>,
+}
 
 pub struct Graph {
     pub nodes: Vec<Entity<GpugNode>>,
@@ -14,6 +70,37 @@ pub struct Graph {
     pub zoom: f32,
     pub pan: Point<Pixels>,
     pub playing: bool,
+    // World-space gravity target; `None` follows the centre of the visible canvas
+    pub center_anchor: Option<(f32, f32)>,
+    pub simulation: SharedSimulation,
+    // How layouts animate nodes to their new positions
+    pub transition_config: TransitionConfig,
+    pub edge_routing: EdgeRouting,
+    // Spacing of the orthogonal layout's grid, which dragged nodes snap to
+    // while `snap_to_grid` is on
+    pub grid_spacing: f32,
+    pub snap_to_grid: bool,
+    // Frame of the last geographic layout, which the coastline is drawn in
+    pub map_frame: Option<MapFrame>,
+    pub show_coastline: bool,
+    // Held by the force simulation and the stress layouts, see `set_constraints`
+    pub constraints: Vec<Constraint>,
+    // Copy of the physics parameters for the UI, so rendering never waits on
+    // the simulation lock
+    force_config: ForceConfig,
+    // Pinned mask last queued for the simulation thread, so unchanged masks
+    // are not sent every frame
+    sent_pinned: Vec<bool>,
+    // Gravity centre last queued for the simulation thread, likewise
+    sent_center: Option<(f32, f32)>,
+    // Background stepping loop; dropping it stops the simulation
+    sim_task: Option<Task<()>>,
+    // One-shot layout being computed off the UI thread, see `apply_layout`
+    layout_task: Option<Task<()>>,
+    // Layout tween in progress, advanced once per frame
+    transition: Option<PositionTransition>,
+    // Routes drawn while `edge_routing` is orthogonal, see `routes_for`
+    route_cache: Option<RouteCache>,
 }
 
 impl Graph {
@@ -27,10 +114,16 @@ impl Graph {
         let zoom = 1.0;
         let pan = point(px(0.0), px(0.0));
         let mut node_entities: Vec<Entity<GpugNode>> = Vec::with_capacity(nodes.len());
+        let positions: Vec<(f32, f32)> = nodes.iter().map(GpugNode::position).collect();
+        let radii: Vec<f32> = nodes.iter().map(|n| n.radius).collect();
+        let mut simulation = ForceSimulation::new(positions, edges.clone());
+        simulation.set_radii(&radii);
+        let simulation = SharedSimulation::new(simulation);
 
         for mut node in nodes {
             node.zoom = zoom;
             node.pan = pan;
+            node.snap_to_grid = None;
             node_entities.push(cx.new(|_| node));
         }
 
@@ -43,7 +136,465 @@ impl Graph {
             zoom,
             pan,
             playing: false,
+            center_anchor: None,
+            simulation,
+            transition_config: TransitionConfig::default(),
+            edge_routing: EdgeRouting::Straight,
+            grid_spacing: OrthogonalConfig::default().grid_spacing,
+            snap_to_grid: false,
+            map_frame: None,
+            show_coastline: true,
+            constraints: Vec::new(),
+            force_config: ForceConfig::default(),
+            sent_pinned: Vec::new(),
+            sent_center: None,
+            sim_task: None,
+            layout_task: None,
+            transition: None,
+            route_cache: None,
+        }
+    }
+
+    // Builder-style override of the default physics parameters
+    pub fn with_force_config(mut self, config: ForceConfig) -> Self {
+        self.simulation.set_config(config);
+        self.force_config = config;
+        self
+    }
+
+    // Builder-style override of how layouts animate; a zero duration disables tweening
+    pub fn with_transition_config(mut self, config: TransitionConfig) -> Self {
+        self.transition_config = config;
+        self
+    }
+
+    pub fn set_transition_config(&mut self, config: TransitionConfig) {
+        self.transition_config = config;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    // The parameters last set from the UI; the simulation thread picks them
+    // up before its next step
+    pub fn force_config(&self) -> ForceConfig {
+        self.force_config
+    }
+
+    pub fn set_force_config(&mut self, config: ForceConfig, cx: &mut Context<Self>) {
+        self.force_config = config;
+        self.simulation.set_config(config);
+        cx.notify();
+    }
+
+    pub fn set_center_anchor(&mut self, anchor: Option<(f32, f32)>, cx: &mut Context<Self>) {
+        self.center_anchor = anchor;
+        if let Some(center) = anchor {
+            self.queue_center(center);
+        }
+        self.simulation.reheat_to(DRAG_ALPHA);
+        cx.notify();
+    }
+
+    fn queue_center(&mut self, (x, y): (f32, f32)) {
+        if self.sent_center != Some((x, y)) {
+            self.sent_center = Some((x, y));
+            self.simulation.set_center(x, y);
+        }
+    }
+
+    // Run a one-shot layout on the background executor and move every node to
+    // its result. Pinned nodes stay put and the simulation is paused so it does
+    // not undo the layout; a later layout replaces one still being computed.
+    pub fn apply_layout(
+        &mut self,
+        mut layout: impl Layout + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let current: Vec<(f32, f32)> = self.nodes.iter().map(|n| n.read(cx).position()).collect();
+        let pinned: Vec<bool> = self.nodes.iter().map(|n| n.read(cx).pinned).collect();
+        let radii: Vec<f32> = self.nodes.iter().map(|n| n.read(cx).radius).collect();
+        let edges = self.edges.clone();
+        self.playing = false;
+        self.sim_task = None;
+        self.map_frame = None;
+        self.edge_routing = EdgeRouting::Straight;
+
+        let compute = cx.background_spawn(async move {
+            let mut positions = layout.layout(&LayoutInput {
+                positions: &current,
+                edges: &edges,
+                pinned: &pinned,
+                radii: &radii,
+            });
+            if positions.len() != current.len() {
+                return None;
+            }
+            if !layout.is_anchored() && !pinned.iter().any(|&p| p) {
+                recenter_on_grid(&mut positions, centroid(&current), layout.grid_spacing());
+            }
+            for (i, p) in positions.iter_mut().enumerate() {
+                if pinned[i] {
+                    *p = current[i];
+                }
+            }
+            Some(positions)
+        });
+        self.layout_task = Some(cx.spawn(async move |this, cx| {
+            let Some(positions) = compute.await else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.layout_task = None;
+                if positions.len() != this.nodes.len() {
+                    return;
+                }
+                this.playing = false;
+                this.sim_task = None;
+                this.set_node_positions(&positions, cx);
+            })
+            .ok();
+        }));
+    }
+
+    // Move the simulation to `positions` and tween nodes there from wherever
+    // they are drawn now, which may be midway through an earlier transition.
+    // Drops any frame the simulation published for the old positions.
+    pub fn set_node_positions(&mut self, positions: &[(f32, f32)], cx: &mut Context<Self>) {
+        if self.transition_config.duration.is_zero() {
+            self.transition = None;
+            self.move_nodes(positions, cx);
+        } else {
+            let from: Vec<(f32, f32)> = self.nodes.iter().map(|n| n.read(cx).position()).collect();
+            self.transition = Some(PositionTransition::new(
+                from,
+                positions.to_vec(),
+                self.transition_config,
+            ));
+        }
+        self.simulation.set_positions(positions.to_vec());
+        cx.notify();
+    }
+
+    fn move_nodes(&self, positions: &[(f32, f32)], cx: &mut Context<Self>) {
+        for (node, &(x, y)) in self.nodes.iter().zip(positions) {
+            cx.update_entity(node, |node, cx| {
+                node.set_position(x, y);
+                cx.notify();
+            });
+        }
+    }
+
+    // Step the running transition to the current frame; returns true while it
+    // needs more frames. Dragging a node or starting the simulation cancels it,
+    // leaving nodes where they were drawn.
+    fn advance_transition(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(transition) = &self.transition else {
+            return false;
+        };
+        let dragging = self.nodes.iter().any(|n| n.read(cx).drag_offset.is_some());
+        if self.playing || dragging {
+            self.transition = None;
+            return false;
+        }
+        let now = Instant::now();
+        let positions = transition.positions_at(now);
+        let finished = transition.is_finished(now);
+        self.move_nodes(&positions, cx);
+        if finished {
+            self.transition = None;
+        }
+        cx.notify();
+        !finished
+    }
+
+    pub fn set_edge_routing(&mut self, routing: EdgeRouting, cx: &mut Context<Self>) {
+        self.edge_routing = routing;
+        cx.notify();
+    }
+
+    // Orthogonal routes between nodes at `centres`, rerouted only when nodes
+    // or edges changed since the last call
+    fn routes_for(&mut self, centres: &[(f32, f32)], radii: &[f32]) -> &[Vec<(f32, f32)>] {
+        let endpoints = self.edges.iter().map(|e| (e.source, e.target));
+        let fresh = self.route_cache.as_ref().is_some_and(|cache| {
+            cache.centres == centres
+                && cache.radii == radii
+                && cache.endpoints.iter().copied().eq(endpoints.clone())
+        });
+        if !fresh {
+            self.route_cache = Some(RouteCache {
+                centres: centres.to_vec(),
+                radii: radii.to_vec(),
+                endpoints: endpoints.collect(),
+                routes: orthogonal_routes(centres, radii, &self.edges),
+            });
         }
+        self.route_cache
+            .as_ref()
+            .map_or(&[], |cache| cache.routes.as_slice())
+    }
+
+    // Make dragged nodes land on multiples of `grid_spacing`
+    pub fn set_snap_to_grid(&mut self, snap: bool, cx: &mut Context<Self>) {
+        self.snap_to_grid = snap;
+        let spacing = snap.then_some(self.grid_spacing);
+        for node in &self.nodes {
+            cx.update_entity(node, |node, _| node.snap_to_grid = spacing);
+        }
+        cx.notify();
+    }
+
+    // Lay nodes out on the grid and draw edges as horizontal and vertical runs
+    pub fn apply_orthogonal_layout(&mut self, cx: &mut Context<Self>) {
+        let layout = OrthogonalLayout::new(OrthogonalConfig {
+            grid_spacing: self.grid_spacing,
+            ..OrthogonalConfig::default()
+        });
+        self.apply_layout(layout, cx);
+        self.set_edge_routing(EdgeRouting::Orthogonal, cx);
+    }
+
+    // Put each part of the graph in its own column, splitting parts by the
+    // node attribute `key` or, without one, detecting them from the edges
+    pub fn apply_multipartite_layout(&mut self, key: Option<&str>, cx: &mut Context<Self>) {
+        let partition = match key {
+            Some(key) => Partition::Attribute(self.node_attributes(key, cx)),
+            None => Partition::Detect,
+        };
+        let layout = MultipartiteLayout::new(MultipartiteConfig {
+            partition,
+            ..MultipartiteConfig::default()
+        });
+        self.apply_layout(layout, cx);
+    }
+
+    // Place nodes on a map by their latitude and longitude attributes, with the
+    // map positioned so located nodes stay centred where the graph is now.
+    // Does nothing when no node has a valid location.
+    pub fn apply_geographic_layout(&mut self, projection: Projection, cx: &mut Context<Self>) {
+        let coordinates = parse_coordinates(
+            &self.node_attributes(LATITUDE_ATTRIBUTE, cx),
+            &self.node_attributes(LONGITUDE_ATTRIBUTE, cx),
+        );
+        let mut frame = MapFrame {
+            projection,
+            ..MapFrame::default()
+        };
+        let projected: Vec<(f32, f32)> = coordinates
+            .iter()
+            .flatten()
+            .map(|&(latitude, longitude)| frame.project(latitude, longitude))
+            .collect();
+        if projected.is_empty() {
+            return;
+        }
+        let current: Vec<(f32, f32)> = self
+            .nodes
+            .iter()
+            .map(|n| {
+                let n = n.read(cx);
+                let (x, y) = n.position();
+                (x + n.radius, y + n.radius)
+            })
+            .collect();
+        let (gx, gy) = centroid(&current);
+        let (mx, my) = centroid(&projected);
+        frame.origin = (gx - mx, gy - my);
+
+        let layout = GeographicLayout::new(GeographicConfig { coordinates, frame });
+        self.apply_layout(layout, cx);
+        self.map_frame = Some(frame);
+    }
+
+    pub fn set_show_coastline(&mut self, show: bool, cx: &mut Context<Self>) {
+        self.show_coastline = show;
+        cx.notify();
+    }
+
+    // Hand the current positions, mid-transition or not, back to the force
+    // simulation
+    pub fn resume_force_layout(&mut self, cx: &mut Context<Self>) {
+        self.playing = true;
+        cx.notify();
+    }
+
+    // Register a custom force with the running simulation
+    pub fn add_force(&mut self, force: Box<dyn Force>, cx: &mut Context<Self>) {
+        self.simulation.add_force(force);
+        self.simulation.reheat_to(1.0);
+        cx.notify();
+    }
+
+    // Remove the first force called `name` from the running simulation
+    pub fn remove_force(&mut self, name: &str, cx: &mut Context<Self>) {
+        self.simulation.remove_force(name);
+        self.simulation.reheat_to(1.0);
+        cx.notify();
+    }
+
+    // Sync node positions into the simulation, warm it to at least `alpha` and
+    // step it on the background executor until it converges or `sim_task` is
+    // dropped
+    fn start_simulation(&mut self, alpha: f32, cx: &mut Context<Self>) {
+        let positions: Vec<(f32, f32)> = self.nodes.iter().map(|n| n.read(cx).position()).collect();
+        let pinned: Vec<bool> = self.nodes.iter().map(|n| n.read(cx).pinned).collect();
+        let radii: Vec<f32> = self.nodes.iter().map(|n| n.read(cx).radius).collect();
+        self.simulation.set_positions(positions);
+        self.simulation.set_pinned_mask(pinned.clone());
+        self.simulation.set_radii(radii);
+        self.simulation.reheat_to(alpha);
+        self.sent_pinned = pinned;
+        let simulation = self.simulation.clone();
+        let executor = cx.background_executor().clone();
+        self.sim_task = Some(cx.background_spawn(async move {
+            while !simulation.tick() {
+                executor.timer(STEP_INTERVAL).await;
+            }
+        }));
+    }
+
+    fn adjust_force<F>(&mut self, cx: &mut Context<Self>, adjust: F)
+    where
+        F: FnOnce(&mut ForceConfig),
+    {
+        let mut config = self.force_config();
+        adjust(&mut config);
+        self.set_force_config(config, cx);
+    }
+
+    // Pin or release a node; pinned nodes keep their position while the layout runs
+    pub fn set_pinned(&mut self, index: usize, pinned: bool, cx: &mut Context<Self>) {
+        let Some(node) = self.nodes.get(index).cloned() else {
+            return;
+        };
+        cx.update_entity(&node, |node, cx| {
+            node.pinned = pinned;
+            cx.notify();
+        });
+        self.simulation.set_pinned(index, pinned);
+        self.simulation.reheat_to(1.0);
+    }
+
+    // Resize a node's disc; the collision force keeps discs from overlapping
+    pub fn set_node_radius(&mut self, index: usize, radius: f32, cx: &mut Context<Self>) {
+        let Some(node) = self.nodes.get(index).cloned() else {
+            return;
+        };
+        let radius = radius.max(1.0);
+        cx.update_entity(&node, |node, cx| {
+            node.radius = radius;
+            cx.notify();
+        });
+        let radii: Vec<f32> = self.nodes.iter().map(|n| n.read(cx).radius).collect();
+        self.simulation.set_radii(radii);
+        self.simulation.reheat_to(1.0);
+        cx.notify();
+    }
+
+    fn selected_nodes(&self, cx: &App) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].read(cx).selected)
+            .collect()
+    }
+
+    fn set_selected_pinned(&mut self, pinned: bool, cx: &mut Context<Self>) {
+        for i in self.selected_nodes(cx) {
+            self.set_pinned(i, pinned, cx);
+        }
+        cx.notify();
+    }
+
+    // Replace the layout constraints. A running simulation is reheated so nodes
+    // move to satisfy them, dragged and pinned nodes holding while the rest
+    // give way; a paused one applies them once played.
+    pub fn set_constraints(&mut self, constraints: Vec<Constraint>, cx: &mut Context<Self>) {
+        self.constraints = constraints;
+        self.simulation.set_constraints(self.constraints.clone());
+        if self.playing {
+            self.simulation.reheat_to(1.0);
+        }
+        cx.notify();
+    }
+
+    pub fn add_constraint(&mut self, constraint: Constraint, cx: &mut Context<Self>) {
+        let mut constraints = self.constraints.clone();
+        constraints.push(constraint);
+        self.set_constraints(constraints, cx);
+    }
+
+    // Line the selected nodes up in a row (`Axis::Y`) or a column (`Axis::X`)
+    pub fn align_selected(&mut self, axis: Axis, cx: &mut Context<Self>) {
+        let nodes = self.selected_nodes(cx);
+        if nodes.len() >= 2 {
+            self.add_constraint(Constraint::Align { axis, nodes }, cx);
+        }
+    }
+
+    // Keep the selected nodes in their current order along `axis`, each at
+    // least `gap` beyond the one before
+    pub fn order_selected(&mut self, axis: Axis, gap: f32, cx: &mut Context<Self>) {
+        let mut nodes = self.selected_nodes(cx);
+        if nodes.len() < 2 {
+            return;
+        }
+        let coordinate = |i: usize| {
+            let (x, y) = self.nodes[i].read(cx).position();
+            match axis {
+                Axis::X => x,
+                Axis::Y => y,
+            }
+        };
+        nodes.sort_by(|&a, &b| coordinate(a).total_cmp(&coordinate(b)));
+        let mut constraints = self.constraints.clone();
+        constraints.extend(nodes.windows(2).map(|pair| Constraint::Separate {
+            axis,
+            before: pair[0],
+            after: pair[1],
+            gap,
+        }));
+        self.set_constraints(constraints, cx);
+    }
+
+    // Keep the selected nodes inside the box their discs span now, grown by
+    // `margin` on every side
+    pub fn box_selected(&mut self, margin: f32, cx: &mut Context<Self>) {
+        let nodes = self.selected_nodes(cx);
+        if nodes.is_empty() {
+            return;
+        }
+        let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+        for &i in &nodes {
+            let node = self.nodes[i].read(cx);
+            let (x, y) = node.position();
+            let size = node.radius * 2.0;
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x + size), max.1.max(y + size));
+        }
+        let min = (min.0 - margin, min.1 - margin);
+        let max = (max.0 + margin, max.1 + margin);
+        self.add_constraint(Constraint::Contain { nodes, min, max }, cx);
+    }
+
+    // Each node's value for `key`, in node order, for attribute-driven layouts
+    pub fn node_attributes(&self, key: &str, cx: &App) -> Vec<Option<String>> {
+        self.nodes
+            .iter()
+            .map(|n| n.read(cx).attribute(key).map(str::to_owned))
+            .collect()
+    }
+
+    // Re-arrange the graph as a spanning tree hanging from the first selected
+    // node, or from the highest-degree node when nothing is selected
+    pub fn layout_tree_from_selected(&mut self, style: TreeStyle, cx: &mut Context<Self>) {
+        let root = (0..self.nodes.len()).find(|&i| self.nodes[i].read(cx).selected);
+        let layout = TreeLayout::new(TreeConfig {
+            style,
+            root,
+            ..TreeConfig::default()
+        });
+        self.apply_layout(layout, cx);
     }
 
     fn max_k(&self) -> usize {
@@ -71,6 +622,8 @@ impl Graph {
         }
         self.k = new_k as usize;
         self.edges = generate_watts_strogatz_graph(node_count, self.k, self.beta);
+        self.simulation.set_edges(self.edges.clone());
+        self.simulation.reheat_to(1.0);
         cx.notify();
     }
 
@@ -85,10 +638,92 @@ impl Graph {
             return;
         }
         self.edges = generate_watts_strogatz_graph(node_count, self.k, self.beta);
+        self.simulation.set_edges(self.edges.clone());
+        self.simulation.reheat_to(1.0);
         cx.notify();
     }
 }
 
+// Label plus -/+ buttons nudging one force parameter by `step`, clamped to `min`
+fn force_parameter_row(
+    label: &str,
+    value: f32,
+    step: f32,
+    min: f32,
+    cx: &mut Context<Graph>,
+    field: fn(&mut ForceConfig) -> &mut f32,
+) -> Div {
+    let decrease = parameter_button("-", cx, move |this, cx| {
+        this.adjust_force(cx, |config| {
+            let v = field(config);
+            *v = (*v - step).max(min);
+        });
+    });
+    let increase = parameter_button("+", cx, move |this, cx| {
+        this.adjust_force(cx, |config| {
+            let v = field(config);
+            *v += step;
+        });
+    });
+    div()
+        .flex()
+        .items_center()
+        .gap_2()
+        .child(format!("{}: {:.3}", label, value))
+        .child(decrease)
+        .child(increase)
+}
+
+// Label plus a button flipping one boolean force parameter
+fn force_toggle_row(
+    label: &str,
+    value: bool,
+    cx: &mut Context<Graph>,
+    field: fn(&mut ForceConfig) -> &mut bool,
+) -> Div {
+    let toggle = parameter_button(if value { "on" } else { "off" }, cx, move |this, cx| {
+        this.adjust_force(cx, |config| {
+            let v = field(config);
+            *v = !*v;
+        });
+    });
+    div()
+        .flex()
+        .items_center()
+        .gap_2()
+        .child(format!("{}:", label))
+        .child(toggle)
+}
+
+// Append a segment of half-width `half_thickness` to `path` as two triangles,
+// extended by `cap` past both ends
+fn push_segment(
+    path: &mut Path<Pixels>,
+    p1: Point<Pixels>,
+    p2: Point<Pixels>,
+    half_thickness: f32,
+    cap: f32,
+) {
+    let dir = point(p2.x - p1.x, p2.y - p1.y);
+    let len = dir.magnitude() as f32;
+    if len <= 0.0001 {
+        return;
+    }
+    let along = dir * (cap / len);
+    let p1 = point(p1.x - along.x, p1.y - along.y);
+    let p2 = point(p2.x + along.x, p2.y + along.y);
+    let normal = point(-dir.y, dir.x) * (half_thickness / len);
+
+    let p1a = point(p1.x + normal.x, p1.y + normal.y);
+    let p1b = point(p1.x - normal.x, p1.y - normal.y);
+    let p2a = point(p2.x + normal.x, p2.y + normal.y);
+    let p2b = point(p2.x - normal.x, p2.y - normal.y);
+
+    let st = (point(0., 1.), point(0., 1.), point(0., 1.));
+    path.push_triangle((p1a, p1b, p2a), st);
+    path.push_triangle((p2a, p1b, p2b), st);
+}
+
 fn parameter_button<F>(label: &str, cx: &mut Context<Graph>, on_press: F) -> Div
 where
     F: Fn(&mut Graph, &mut Context<Graph>) + 'static,
@@ -117,38 +752,49 @@ impl Render for Graph {
         let nodes = self.nodes.clone();
         let edges = self.edges.clone();
         let graph_entity = graph_cx.entity();
+        let routing = self.edge_routing;
+        let routes_graph = graph_entity.clone();
         let edges_canvas = canvas(
             |_bounds, _window, _cx| (),
             move |_bounds, _state, window, cx| {
                 let mut path = gpui::Path::new(point(px(0.0), px(0.0)));
                 let thickness = (0.5f32 * zoom).max(0.5);
-                for edge in &edges {
-                    let i = edge.source;
-                    let j = edge.target;
-                    if i >= nodes.len() || j >= nodes.len() {
-                        continue;
+                let (centres, radii): (Vec<(f32, f32)>, Vec<f32>) = nodes
+                    .iter()
+                    .map(|n| {
+                        cx.read_entity(n, |n, _| {
+                            let (x, y) = n.position();
+                            ((x + n.radius, y + n.radius), n.radius)
+                        })
+                    })
+                    .unzip();
+                let to_screen =
+                    |(x, y): (f32, f32)| point(pan.x + px(x) * zoom, pan.y + px(y) * zoom);
+                match routing {
+                    EdgeRouting::Straight => {
+                        for edge in &edges {
+                            let i = edge.source;
+                            let j = edge.target;
+                            if i >= nodes.len() || j >= nodes.len() {
+                                continue;
+                            }
+                            let p1 = to_screen(centres[i]);
+                            let p2 = to_screen(centres[j]);
+                            push_segment(&mut path, p1, p2, thickness, 0.0);
+                        }
                     }
-                    let (x1, y1) = cx.read_entity(&nodes[i], |n, _| (n.x + px(8.0), n.y + px(8.0)));
-                    let (x2, y2) = cx.read_entity(&nodes[j], |n, _| (n.x + px(8.0), n.y + px(8.0)));
-
-                    let p1 = point(pan.x + x1 * zoom, pan.y + y1 * zoom);
-                    let p2 = point(pan.x + x2 * zoom, pan.y + y2 * zoom);
-                    let dir = point(p2.x - p1.x, p2.y - p1.y);
-                    let len = dir.magnitude() as f32;
-                    if len <= 0.0001 {
-                        continue;
+                    EdgeRouting::Orthogonal => {
+                        cx.update_entity(&routes_graph, |g: &mut Graph, _| {
+                            for route in g.routes_for(&centres, &radii) {
+                                for segment in route.windows(2) {
+                                    let p1 = to_screen(segment[0]);
+                                    let p2 = to_screen(segment[1]);
+                                    // Square caps close the gap at each bend
+                                    push_segment(&mut path, p1, p2, thickness, thickness);
+                                }
+                            }
+                        });
                     }
-                    let half_thickness: f32 = thickness as f32;
-                    let normal = point(-dir.y, dir.x) * (half_thickness / len);
-
-                    let p1a = point(p1.x + normal.x, p1.y + normal.y);
-                    let p1b = point(p1.x - normal.x, p1.y - normal.y);
-                    let p2a = point(p2.x + normal.x, p2.y + normal.y);
-                    let p2b = point(p2.x - normal.x, p2.y - normal.y);
-
-                    let st = (point(0., 1.), point(0., 1.), point(0., 1.));
-                    path.push_triangle((p1a, p1b, p2a), st);
-                    path.push_triangle((p2a, p1b, p2b), st);
                 }
                 window.paint_path(path, rgb(0x323232));
             },
@@ -156,9 +802,39 @@ impl Render for Graph {
         .absolute()
         .size_full();
 
+        // Coastline beneath the edges, in the same frame the geographic layout
+        // centres nodes in
+        let coastline_frame = self.map_frame.filter(|_| self.show_coastline);
+        let coastline_canvas = canvas(
+            |_bounds, _window, _cx| (),
+            move |_bounds, _state, window, _cx| {
+                let Some(frame) = coastline_frame else {
+                    return;
+                };
+                let mut path = gpui::Path::new(point(px(0.0), px(0.0)));
+                let thickness = (0.75f32 * zoom).max(0.5);
+                let to_screen = |latitude: f32, longitude: f32| {
+                    let (x, y) = frame.project(latitude, longitude);
+                    point(pan.x + px(x) * zoom, pan.y + px(y) * zoom)
+                };
+                for ring in coastline() {
+                    let points: Vec<Point<Pixels>> =
+                        ring.iter().map(|&(lat, lon)| to_screen(lat, lon)).collect();
+                    for (k, &p1) in points.iter().enumerate() {
+                        let p2 = points[(k + 1) % points.len()];
+                        push_segment(&mut path, p1, p2, thickness, thickness);
+                    }
+                }
+                window.paint_path(path, rgb(0xa9c6de));
+            },
+        )
+        .absolute()
+        .size_full();
+
         // Node entities render above edges
         let graph_canvas = div()
             .size_full()
+            .child(coastline_canvas)
             .child(edges_canvas)
             .children(self.nodes.iter().cloned());
 
@@ -178,6 +854,247 @@ impl Render for Graph {
                 this.adjust_beta(beta_step, cx);
             });
 
+            let config = self.force_config();
+            let repulsion_row =
+                force_parameter_row("repulsion", config.repulsion, 10.0, 0.0, graph_cx, |c| {
+                    &mut c.repulsion
+                });
+            let attraction_row =
+                force_parameter_row("attraction", config.attraction, 0.005, 0.0, graph_cx, |c| {
+                    &mut c.attraction
+                });
+            let gravity_row =
+                force_parameter_row("gravity", config.gravity, 0.001, 0.0, graph_cx, |c| {
+                    &mut c.gravity
+                });
+            let pin_selected = parameter_button("pin", graph_cx, |this, cx| {
+                this.set_selected_pinned(true, cx);
+            });
+            let unpin_selected = parameter_button("unpin", graph_cx, |this, cx| {
+                this.set_selected_pinned(false, cx);
+            });
+            let tree_from_selected = parameter_button("tree from here", graph_cx, |this, cx| {
+                this.layout_tree_from_selected(TreeStyle::Tidy, cx);
+            });
+            let radial_from_selected =
+                parameter_button("radial from here", graph_cx, |this, cx| {
+                    this.layout_tree_from_selected(TreeStyle::Radial, cx);
+                });
+
+            let toggle_strategy = parameter_button("switch", graph_cx, |this, cx| {
+                this.adjust_force(cx, |config| {
+                    config.repulsion_strategy = match config.repulsion_strategy {
+                        RepulsionStrategy::Grid => RepulsionStrategy::BarnesHut,
+                        RepulsionStrategy::BarnesHut => RepulsionStrategy::Grid,
+                    };
+                });
+            });
+            let multilevel = parameter_button("multilevel", graph_cx, |this, cx| {
+                this.apply_layout(MultilevelLayout::default(), cx);
+            });
+            let fruchterman_reingold = parameter_button("fr", graph_cx, |this, cx| {
+                this.apply_layout(FruchtermanReingoldLayout::default(), cx);
+            });
+            let kamada_kawai = parameter_button("kk", graph_cx, |this, cx| {
+                this.apply_layout(KamadaKawaiLayout::default(), cx);
+            });
+            let stress = parameter_button("stress", graph_cx, |this, cx| {
+                let config = StressConfig {
+                    constraints: this.constraints.clone(),
+                    ..StressConfig::default()
+                };
+                this.apply_layout(StressLayout::new(config), cx);
+            });
+            let sparse_stress = parameter_button("sparse stress", graph_cx, |this, cx| {
+                let config = StressConfig {
+                    pivots: Some(SPARSE_STRESS_PIVOTS),
+                    constraints: this.constraints.clone(),
+                    ..StressConfig::default()
+                };
+                this.apply_layout(StressLayout::new(config), cx);
+            });
+            let spectral = parameter_button("spectral", graph_cx, |this, cx| {
+                this.apply_layout(SpectralLayout::default(), cx);
+            });
+            let circular = parameter_button("circular", graph_cx, |this, cx| {
+                this.apply_layout(CircularLayout::default(), cx);
+            });
+            let circular_uncrossed =
+                parameter_button("circular min-cross", graph_cx, |this, cx| {
+                    let config = CircularConfig {
+                        reduce_crossings: true,
+                        ..CircularConfig::default()
+                    };
+                    this.apply_layout(CircularLayout::new(config), cx);
+                });
+            let shell = parameter_button("shell", graph_cx, |this, cx| {
+                this.apply_layout(ShellLayout::default(), cx);
+            });
+            let multipartite = parameter_button("multipartite", graph_cx, |this, cx| {
+                let by_attribute = this
+                    .nodes
+                    .iter()
+                    .any(|n| n.read(cx).attribute(PARTITION_ATTRIBUTE).is_some());
+                this.apply_multipartite_layout(by_attribute.then_some(PARTITION_ATTRIBUTE), cx);
+            });
+            let force = parameter_button("force", graph_cx, |this, cx| {
+                this.resume_force_layout(cx);
+            });
+            let orthogonal = parameter_button("orthogonal", graph_cx, |this, cx| {
+                this.apply_orthogonal_layout(cx);
+            });
+            let toggle_routing = parameter_button("switch", graph_cx, |this, cx| {
+                let routing = match this.edge_routing {
+                    EdgeRouting::Straight => EdgeRouting::Orthogonal,
+                    EdgeRouting::Orthogonal => EdgeRouting::Straight,
+                };
+                this.set_edge_routing(routing, cx);
+            });
+            let routing_label = match self.edge_routing {
+                EdgeRouting::Straight => "straight",
+                EdgeRouting::Orthogonal => "orthogonal",
+            };
+            let toggle_snap = parameter_button(
+                if self.snap_to_grid { "on" } else { "off" },
+                graph_cx,
+                |this, cx| {
+                    this.set_snap_to_grid(!this.snap_to_grid, cx);
+                },
+            );
+            let mercator = parameter_button("mercator", graph_cx, |this, cx| {
+                this.apply_geographic_layout(Projection::WebMercator, cx);
+            });
+            let equirectangular = parameter_button("equirectangular", graph_cx, |this, cx| {
+                this.apply_geographic_layout(Projection::Equirectangular, cx);
+            });
+            let toggle_coastline = parameter_button(
+                if self.show_coastline { "on" } else { "off" },
+                graph_cx,
+                |this, cx| {
+                    this.set_show_coastline(!this.show_coastline, cx);
+                },
+            );
+            let align_row = parameter_button("row", graph_cx, |this, cx| {
+                this.align_selected(Axis::Y, cx);
+            });
+            let align_column = parameter_button("column", graph_cx, |this, cx| {
+                this.align_selected(Axis::X, cx);
+            });
+            let order_left_right = parameter_button("left to right", graph_cx, |this, cx| {
+                this.order_selected(Axis::X, ORDER_GAP, cx);
+            });
+            let order_top_bottom = parameter_button("top to bottom", graph_cx, |this, cx| {
+                this.order_selected(Axis::Y, ORDER_GAP, cx);
+            });
+            let keep_in_box = parameter_button("keep in box", graph_cx, |this, cx| {
+                this.box_selected(BOX_MARGIN, cx);
+            });
+            let clear_constraints = parameter_button("clear", graph_cx, |this, cx| {
+                this.set_constraints(Vec::new(), cx);
+            });
+            let layered_top_bottom = parameter_button("layered tb", graph_cx, |this, cx| {
+                this.apply_layout(LayeredLayout::default(), cx);
+            });
+            let layered_left_right = parameter_button("layered lr", graph_cx, |this, cx| {
+                let config = LayeredConfig {
+                    direction: LayerDirection::LeftToRight,
+                    ..LayeredConfig::default()
+                };
+                this.apply_layout(LayeredLayout::new(config), cx);
+            });
+
+            let toggle_centering = parameter_button("switch", graph_cx, |this, cx| {
+                this.adjust_force(cx, |config| {
+                    config.centering = match config.centering {
+                        CenteringMode::Pull => CenteringMode::Translate,
+                        CenteringMode::Translate => CenteringMode::Pull,
+                    };
+                });
+            });
+            let centering_label = match config.centering {
+                CenteringMode::Pull => "pull",
+                CenteringMode::Translate => "translate",
+            };
+            let strategy_label = match config.repulsion_strategy {
+                RepulsionStrategy::Grid => "grid",
+                RepulsionStrategy::BarnesHut => "barnes-hut",
+            };
+            let collision_row = force_parameter_row(
+                "collision",
+                config.collision_strength,
+                0.1,
+                0.0,
+                graph_cx,
+                |c| &mut c.collision_strength,
+            );
+            let damping_row =
+                force_parameter_row("damping", config.damping, 0.05, 0.05, graph_cx, |c| {
+                    &mut c.damping
+                });
+
+            let toggle_model = parameter_button("switch", graph_cx, |this, cx| {
+                this.adjust_force(cx, |config| {
+                    config.model = match config.model {
+                        ForceModel::Classic => ForceModel::ForceAtlas2,
+                        ForceModel::ForceAtlas2 => ForceModel::Classic,
+                    };
+                });
+            });
+            let model_label = match config.model {
+                ForceModel::Classic => "classic",
+                ForceModel::ForceAtlas2 => "forceatlas2",
+            };
+            // Only the active model's parameters are shown
+            let model_rows: Vec<Div> = match config.model {
+                ForceModel::Classic => vec![
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(format!("repulsion mode: {}", strategy_label))
+                        .child(toggle_strategy),
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(format!("centering: {}", centering_label))
+                        .child(toggle_centering),
+                    repulsion_row,
+                    attraction_row,
+                    gravity_row,
+                    collision_row,
+                    damping_row,
+                ],
+                ForceModel::ForceAtlas2 => {
+                    let fa2 = config.force_atlas2;
+                    vec![
+                        force_parameter_row(
+                            "scaling",
+                            fa2.scaling_ratio,
+                            0.5,
+                            0.5,
+                            graph_cx,
+                            |c| &mut c.force_atlas2.scaling_ratio,
+                        ),
+                        force_parameter_row("gravity", fa2.gravity, 0.1, 0.0, graph_cx, |c| {
+                            &mut c.force_atlas2.gravity
+                        }),
+                        force_toggle_row("linlog", fa2.lin_log, graph_cx, |c| {
+                            &mut c.force_atlas2.lin_log
+                        }),
+                        force_toggle_row("prevent overlap", fa2.prevent_overlap, graph_cx, |c| {
+                            &mut c.force_atlas2.prevent_overlap
+                        }),
+                        force_toggle_row("dissuade hubs", fa2.dissuade_hubs, graph_cx, |c| {
+                            &mut c.force_atlas2.dissuade_hubs
+                        }),
+                        force_toggle_row("strong gravity", fa2.strong_gravity, graph_cx, |c| {
+                            &mut c.force_atlas2.strong_gravity
+                        }),
+                    ]
+                }
+            };
+
             div()
                 .absolute()
                 .top(px(8.0))
@@ -208,145 +1125,191 @@ impl Render for Graph {
                         .child(decrease_beta)
                         .child(increase_beta),
                 )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child("selected:")
+                        .child(pin_selected)
+                        .child(unpin_selected)
+                        .child(tree_from_selected)
+                        .child(radial_from_selected),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(format!("model: {}", model_label))
+                        .child(toggle_model),
+                )
+                .children(model_rows)
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child("layout:")
+                        .child(multilevel)
+                        .child(fruchterman_reingold)
+                        .child(kamada_kawai)
+                        .child(stress)
+                        .child(sparse_stress)
+                        .child(layered_top_bottom)
+                        .child(layered_left_right)
+                        .child(spectral)
+                        .child(circular)
+                        .child(circular_uncrossed)
+                        .child(shell)
+                        .child(orthogonal)
+                        .child(multipartite)
+                        .child(force),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(format!("edges: {}", routing_label))
+                        .child(toggle_routing)
+                        .child("snap to grid:")
+                        .child(toggle_snap),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child("map:")
+                        .child(mercator)
+                        .child(equirectangular)
+                        .child("coastline:")
+                        .child(toggle_coastline),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(format!("constraints ({}):", self.constraints.len()))
+                        .child(align_row)
+                        .child(align_column)
+                        .child(order_left_right)
+                        .child(order_top_bottom)
+                        .child(keep_in_box)
+                        .child(clear_constraints),
+                )
         };
 
-        // Simulation canvas: runs a physics step per frame when playing
+        // Simulation canvas: advances layout transitions and, while playing,
+        // forwards drags to the simulation thread and swaps in the latest
+        // published positions each frame
         let graph_handle = graph_entity.clone();
         let nodes_for_sim = self.nodes.clone();
-        let edges = self.edges.clone();
+        let simulation = self.simulation.clone();
         let sim_canvas = canvas(
             move |_bounds, _window, _cx| (),
-            move |_bounds, _state, window, cx| {
-                let playing = cx.read_entity(&graph_handle, |g: &Graph, _| g.playing);
+            move |bounds, _state, window, cx| {
+                // Gravity targets the anchor or the canvas centre in world space,
+                // offset so node discs rather than their corners are centred
+                let (pan, zoom, anchor) = cx.read_entity(&graph_handle, |g: &Graph, _| {
+                    (g.pan, g.zoom, g.center_anchor)
+                });
+                let center = anchor.unwrap_or_else(|| {
+                    let c = bounds.center();
+                    (
+                        ((c.x - pan.x) / zoom / px(1.0)) as f32 - DEFAULT_NODE_RADIUS,
+                        ((c.y - pan.y) / zoom / px(1.0)) as f32 - DEFAULT_NODE_RADIUS,
+                    )
+                });
+                cx.update_entity(&graph_handle, |g: &mut Graph, _| g.queue_center(center));
+
+                let animating =
+                    cx.update_entity(&graph_handle, |g: &mut Graph, cx| g.advance_transition(cx));
+                if animating {
+                    window.request_animation_frame();
+                }
+
+                let playing = cx.update_entity(&graph_handle, |g: &mut Graph, cx| {
+                    if !g.playing {
+                        g.sim_task = None;
+                    } else if g.sim_task.is_none() {
+                        g.start_simulation(1.0, cx);
+                    }
+                    g.playing
+                });
                 if !playing {
                     return;
                 }
-                let n = nodes_for_sim.len();
-                if n == 0 {
+                if nodes_for_sim.is_empty() {
                     return;
                 }
 
                 window.request_animation_frame();
 
-                // Read positions
-                let mut xs: Vec<f32> = Vec::with_capacity(n);
-                let mut ys: Vec<f32> = Vec::with_capacity(n);
-                for ent in &nodes_for_sim {
-                    let (x, y) = cx.read_entity(ent, |nd, _| (nd.x, nd.y));
-                    xs.push((x / px(1.0)) as f32);
-                    ys.push((y / px(1.0)) as f32);
-                }
-
-                let mut fx = vec![0.0f32; n];
-                let mut fy = vec![0.0f32; n];
-
-                // Force parameters (tune for stability/perf)
-                let repulsion = 120.0f32; // lower repulsion reduces oscillation
-                let attraction = 0.03f32; // stronger springs for faster settling
-                let gravity = 0.006f32; // pull toward center
-                let damping = 0.85f32; // velocity damping
-                let dt = 0.5f32; // larger step, clamped below
-                let max_disp = 5.0f32; // cap displacement per step
-                let center_x = 800.0f32;
-                let center_y = 200.0f32;
-
-                // Spatial grid for approximate repulsion
-                use std::collections::HashMap;
-                let cell = 100.0f32;
-                let mut bins: HashMap<(i32, i32), Vec<usize>> = HashMap::with_capacity(n * 2);
-                for i in 0..n {
-                    let gx = (xs[i] / cell).floor() as i32;
-                    let gy = (ys[i] / cell).floor() as i32;
-                    bins.entry((gx, gy)).or_default().push(i);
-                }
-                let neighbors = [
-                    (-1, -1),
-                    (0, -1),
-                    (1, -1),
-                    (-1, 0),
-                    (0, 0),
-                    (1, 0),
-                    (-1, 1),
-                    (0, 1),
-                    (1, 1),
-                ];
-                for i in 0..n {
-                    let gx = (xs[i] / cell).floor() as i32;
-                    let gy = (ys[i] / cell).floor() as i32;
-                    for (dxg, dyg) in neighbors {
-                        if let Some(v) = bins.get(&(gx + dxg, gy + dyg)) {
-                            for &j in v {
-                                if j <= i {
-                                    continue;
-                                }
-                                let dx = xs[j] - xs[i];
-                                let dy = ys[j] - ys[i];
-                                let d2 = dx * dx + dy * dy + 0.01;
-                                let inv = 1.0 / d2;
-                                let fx_ij = repulsion * dx * inv;
-                                let fy_ij = repulsion * dy * inv;
-                                fx[i] -= fx_ij;
-                                fy[i] -= fy_ij;
-                                fx[j] += fx_ij;
-                                fy[j] += fy_ij;
-                            }
-                        }
+                // Forward in-progress drags to the simulation thread, pinning
+                // dragged nodes so the physics does not fight the cursor
+                let mut dragging = false;
+                let mut pinned: Vec<bool> = Vec::with_capacity(nodes_for_sim.len());
+                for (i, ent) in nodes_for_sim.iter().enumerate() {
+                    let (dragged, is_pinned) = cx.read_entity(ent, |nd, _| {
+                        (nd.drag_offset.map(|_| nd.position()), nd.pinned)
+                    });
+                    if let Some((x, y)) = dragged {
+                        simulation.push_position(i, x, y);
+                        dragging = true;
                     }
+                    pinned.push(is_pinned || dragged.is_some());
                 }
-
-                // Attraction along edges
-                for edge in &edges {
-                    let i = edge.source;
-                    let j = edge.target;
-                    if i >= n || j >= n {
-                        continue;
+                let changed = cx.update_entity(&graph_handle, |g: &mut Graph, _| {
+                    let changed = g.sent_pinned != pinned;
+                    if changed {
+                        g.sent_pinned = pinned.clone();
                     }
-                    let dx = xs[j] - xs[i];
-                    let dy = ys[j] - ys[i];
-                    let fx_e = attraction * dx;
-                    let fy_e = attraction * dy;
-                    fx[i] += fx_e;
-                    fy[i] += fy_e;
-                    fx[j] -= fx_e;
-                    fy[j] -= fy_e;
+                    changed
+                });
+                if changed {
+                    simulation.set_pinned_mask(pinned);
                 }
-
-                // Gravity towards center
-                for i in 0..n {
-                    fx[i] += gravity * (center_x - xs[i]);
-                    fy[i] += gravity * (center_y - ys[i]);
+                if dragging {
+                    // Keep the layout warm so neighbours follow the dragged node
+                    simulation.reheat_to(DRAG_ALPHA);
                 }
 
-                // Integrate and clamp small step
-                for i in 0..n {
-                    let mut dx = fx[i] * dt;
-                    let mut dy = fy[i] * dt;
-                    dx *= damping;
-                    dy *= damping;
-                    let disp2 = dx * dx + dy * dy;
-                    if disp2 > max_disp * max_disp {
-                        let s = max_disp / disp2.sqrt();
-                        dx *= s;
-                        dy *= s;
-                    }
-                    xs[i] += dx;
-                    ys[i] += dy;
-                }
+                let Some(snapshot) = simulation.take_snapshot() else {
+                    return;
+                };
 
-                // Write back
-                for i in 0..n {
-                    let nx = px(xs[i] as f32);
-                    let ny = px(ys[i] as f32);
-                    let ent = nodes_for_sim[i].clone();
-                    cx.update_entity(&ent, move |node, _| {
-                        node.x = nx;
-                        node.y = ny;
+                // Write back; a dragged node follows the cursor rather than the snapshot
+                for (ent, &(x, y)) in nodes_for_sim.iter().zip(&snapshot.positions) {
+                    cx.update_entity(ent, move |node, _| {
+                        if node.drag_offset.is_none() {
+                            node.x = px(x);
+                            node.y = px(y);
+                        }
                     });
                 }
-                // Bookkeep a tick so any observers can react and mark the graph dirty
-                cx.update_entity(&graph_handle, |g: &mut Graph, _| {
-                    g.sim_tick = g.sim_tick.wrapping_add(1);
+
+                // Bookkeep the tick so any observers can react
+                cx.update_entity(&graph_handle, |g: &mut Graph, cx| {
+                    g.sim_tick = snapshot.tick;
+                    if snapshot.converged {
+                        if dragging {
+                            // Restart only as warm as the drag keeps it, so
+                            // the rest of the layout does not jump
+                            g.start_simulation(DRAG_ALPHA, cx);
+                        } else {
+                            // Stop requesting frames once nothing is moving
+                            g.playing = false;
+                            g.sim_task = None;
+                            cx.emit(GraphEvent::LayoutConverged {
+                                sim_tick: g.sim_tick,
+                            });
+                        }
+                    }
                 });
+                // Mark the graph dirty so edges follow the new positions
                 cx.notify(graph_handle.entity_id());
             },
         )
@@ -370,6 +1333,7 @@ impl Render for Graph {
                 gpui::MouseButton::Left,
                 graph_cx.listener({
                     move |this, _e: &gpui::MouseDownEvent, _w, cx| {
+                        // The simulation canvas starts or stops the background loop
                         this.playing = !this.playing;
                         cx.notify();
                     }
@@ -387,10 +1351,11 @@ impl Render for Graph {
                     let cursor = e.position;
                     let mut hit_index: Option<usize> = None;
                     for (i, n) in this.nodes.iter().enumerate() {
-                        let (nx, ny) = cx.read_entity(n, |node, _| (node.x, node.y));
+                        let (nx, ny, radius) =
+                            cx.read_entity(n, |node, _| (node.x, node.y, node.radius));
                         let left = this.pan.x + nx * this.zoom;
                         let top = this.pan.y + ny * this.zoom;
-                        let size = px(16.0) * this.zoom;
+                        let size = px(radius * 2.0) * this.zoom;
                         if cursor.x >= left
                             && cursor.x <= left + size
                             && cursor.y >= top
@@ -0,0 +1,274 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::f32::consts::TAU;
+
+use crate::layout::{adjacency, attribute_ranks, centroid, Layout, LayoutInput};
+
+// Order of the nodes around the circle
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircularOrder {
+    Index,
+    // One value per node, e.g. from `Graph::node_attributes`; nodes are
+    // sorted by value and ties keep index order
+    Attribute(Vec<Option<String>>),
+}
+
+// Parameters of the circular layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircularConfig {
+    pub order: CircularOrder,
+    // Reorder nodes towards their neighbours to cut chord crossings. With an
+    // attribute order nodes only move among nodes sharing their value.
+    pub reduce_crossings: bool,
+    // Passes of crossing reduction
+    pub sweeps: usize,
+    // Arc length between neighbouring nodes; sets the radius
+    pub node_spacing: f32,
+    pub min_radius: f32,
+}
+
+impl Default for CircularConfig {
+    fn default() -> Self {
+        Self {
+            order: CircularOrder::Index,
+            reduce_crossings: false,
+            sweeps: 8,
+            node_spacing: 30.0,
+            min_radius: 100.0,
+        }
+    }
+}
+
+// Places every node on one circle around the input centroid; ring lattices
+// such as Watts-Strogatz graphs show up as their ring
+#[derive(Default)]
+pub struct CircularLayout {
+    pub config: CircularConfig,
+}
+
+impl CircularLayout {
+    pub fn new(config: CircularConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for CircularLayout {
+    fn name(&self) -> &str {
+        "circular"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        circular_layout(input, &self.config)
+    }
+}
+
+pub fn circular_layout(input: &LayoutInput, config: &CircularConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let groups: Vec<usize> = match &config.order {
+        CircularOrder::Index => vec![0; n],
+        CircularOrder::Attribute(values) if values.len() == n => attribute_ranks(values),
+        CircularOrder::Attribute(_) => vec![0; n],
+    };
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&v| groups[v]);
+    if config.reduce_crossings {
+        order = reduce_crossings(order, &groups, input, config.sweeps);
+    }
+
+    let radius = (n as f32 * config.node_spacing / TAU).max(config.min_radius);
+    let (cx, cy) = centroid(input.positions);
+    let mut positions = vec![(0.0, 0.0); n];
+    for (slot, &v) in order.iter().enumerate() {
+        let angle = TAU * slot as f32 / n as f32;
+        positions[v] = (cx + radius * angle.cos(), cy + radius * angle.sin());
+    }
+    positions
+}
+
+// Greedy connectivity order (after Baur and Brandes): repeatedly append the
+// node with most already placed neighbours, then barycentric sweeps moving each
+// node towards the circular mean angle of its neighbours. Groups stay
+// contiguous and the order with fewest chord crossings wins.
+fn reduce_crossings(
+    order: Vec<usize>,
+    groups: &[usize],
+    input: &LayoutInput,
+    sweeps: usize,
+) -> Vec<usize> {
+    let n = order.len();
+    let neighbors = adjacency(n, input.edges);
+    let mut slot = vec![0usize; n];
+    let index = |order: &[usize], slot: &mut [usize]| {
+        for (s, &v) in order.iter().enumerate() {
+            slot[v] = s;
+        }
+        count_chord_crossings(slot, input)
+    };
+    let mut best_crossings = index(&order, &mut slot);
+    let mut best = order;
+
+    let mut current = greedy_order(&neighbors);
+    current.sort_by_key(|&v| groups[v]);
+    let crossings = index(&current, &mut slot);
+    if crossings < best_crossings {
+        best_crossings = crossings;
+        best = current.clone();
+    }
+
+    for _ in 0..sweeps {
+        if best_crossings == 0 {
+            break;
+        }
+        let angle: Vec<f32> = (0..n)
+            .map(|v| {
+                let own = TAU * slot[v] as f32 / n as f32;
+                if neighbors[v].is_empty() {
+                    return own;
+                }
+                let (sin, cos) = neighbors[v].iter().fold((0.0f32, 0.0f32), |(s, c), &u| {
+                    let a = TAU * slot[u] as f32 / n as f32;
+                    (s + a.sin(), c + a.cos())
+                });
+                // Opposite neighbours cancel out; stay put rather than jump
+                if sin.abs() + cos.abs() < 1e-4 {
+                    own
+                } else {
+                    sin.atan2(cos).rem_euclid(TAU)
+                }
+            })
+            .collect();
+        current.sort_by(|&a, &b| {
+            groups[a]
+                .cmp(&groups[b])
+                .then(angle[a].total_cmp(&angle[b]))
+        });
+        let crossings = index(&current, &mut slot);
+        if crossings < best_crossings {
+            best_crossings = crossings;
+            best = current.clone();
+        }
+    }
+    best
+}
+
+// Visit order that always continues with the unplaced node having the most
+// placed neighbours, preferring fewer unplaced ones, so connected nodes end
+// up next to each other
+fn greedy_order(neighbors: &[Vec<usize>]) -> Vec<usize> {
+    let n = neighbors.len();
+    let mut placed_neighbors = vec![0usize; n];
+    let mut placed = vec![false; n];
+    let mut heap: BinaryHeap<(usize, Reverse<usize>, Reverse<usize>)> = BinaryHeap::new();
+    let mut order = Vec::with_capacity(n);
+    let mut next_start = 0;
+    while order.len() < n {
+        let v = match heap.pop() {
+            Some((count, _, Reverse(v))) if !placed[v] && count == placed_neighbors[v] => v,
+            Some(_) => continue,
+            None => {
+                // New component: start from its lowest-degree unplaced node
+                while placed[next_start] {
+                    next_start += 1;
+                }
+                (next_start..n)
+                    .filter(|&v| !placed[v])
+                    .min_by_key(|&v| neighbors[v].len())
+                    .unwrap_or(next_start)
+            }
+        };
+        placed[v] = true;
+        order.push(v);
+        for &u in &neighbors[v] {
+            if !placed[u] {
+                placed_neighbors[u] += 1;
+                let unplaced = neighbors[u].len() - placed_neighbors[u];
+                heap.push((placed_neighbors[u], Reverse(unplaced), Reverse(u)));
+            }
+        }
+    }
+    order
+}
+
+// Pairs of edges whose chords cross, i.e. endpoints interleave as a < c < b < d
+// around the circle. Counted with a Fenwick tree over chord ends.
+fn count_chord_crossings(slot: &[usize], input: &LayoutInput) -> usize {
+    let n = slot.len();
+    let mut chords: Vec<(usize, usize)> = input
+        .edges
+        .iter()
+        .filter(|e| e.source < n && e.target < n && e.source != e.target)
+        .map(|e| {
+            let (a, b) = (slot[e.source], slot[e.target]);
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    chords.sort_unstable();
+    chords.dedup();
+
+    let mut tree = vec![0usize; n + 1];
+    let prefix = |tree: &[usize], mut i: usize| {
+        let mut total = 0;
+        while i > 0 {
+            total += tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        total
+    };
+    let mut total = 0;
+    let mut start = 0;
+    while start < chords.len() {
+        // Chords sharing a start point never cross each other
+        let a = chords[start].0;
+        let mut end = start;
+        while end < chords.len() && chords[end].0 == a {
+            let b = chords[end].1;
+            // Earlier chords ending strictly between a and b cross this one
+            total += prefix(&tree, b) - prefix(&tree, a + 1);
+            end += 1;
+        }
+        for &(_, b) in &chords[start..end] {
+            let mut i = b + 1;
+            while i <= n {
+                tree[i] += 1;
+                i += i & i.wrapping_neg();
+            }
+        }
+        start = end;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    fn crossings(n: usize, pairs: &[(usize, usize)]) -> usize {
+        let positions = vec![(0.0, 0.0); n];
+        let edges: Vec<GpugEdge> = pairs.iter().map(|&(u, v)| GpugEdge::new(u, v)).collect();
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &[],
+        };
+        let slot: Vec<usize> = (0..n).collect();
+        count_chord_crossings(&slot, &input)
+    }
+
+    #[test]
+    fn chord_crossings_of_known_drawings() {
+        assert_eq!(crossings(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]), 0);
+        assert_eq!(crossings(4, &[(0, 2), (1, 3)]), 1);
+        // Chords sharing an endpoint never cross
+        assert_eq!(crossings(4, &[(0, 2), (0, 3), (2, 0)]), 0);
+        // Every 4 of the 5 points give one crossing
+        let k5: Vec<(usize, usize)> = (0..5)
+            .flat_map(|i| ((i + 1)..5).map(move |j| (i, j)))
+            .collect();
+        assert_eq!(crossings(5, &k5), 5);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::generators::utils::rand_f32;
+use crate::layout::{adjacency, centroid, Layout, LayoutInput};
+
+// Parameters of the Fruchterman-Reingold layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FruchtermanReingoldConfig {
+    // Ideal distance k between connected nodes
+    pub optimal_distance: f32,
+    pub iterations: usize,
+    // Largest move allowed in the first iteration; defaults to k * sqrt(n) / 10
+    pub initial_temperature: Option<f32>,
+    // Seeds the starting positions so the same graph always gives the same layout
+    pub seed: u64,
+}
+
+impl Default for FruchtermanReingoldConfig {
+    fn default() -> Self {
+        Self {
+            optimal_distance: 50.0,
+            iterations: 300,
+            initial_temperature: None,
+            seed: 0xF00D_FACE_0BAD_CAFE,
+        }
+    }
+}
+
+// Fruchterman-Reingold (1991): all-pairs repulsion k^2 / d, attraction d^2 / k
+// along edges, and moves capped by a temperature that cools linearly to zero
+#[derive(Default)]
+pub struct FruchtermanReingoldLayout {
+    pub config: FruchtermanReingoldConfig,
+}
+
+impl FruchtermanReingoldLayout {
+    pub fn new(config: FruchtermanReingoldConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for FruchtermanReingoldLayout {
+    fn name(&self) -> &str {
+        "fruchterman-reingold"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        fruchterman_reingold(input, &self.config)
+    }
+}
+
+// Deterministic for a given config: free nodes start from seeded positions
+// around the input centroid, pinned nodes keep their input position
+pub fn fruchterman_reingold(
+    input: &LayoutInput,
+    config: &FruchtermanReingoldConfig,
+) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n < 2 {
+        return input.positions.to_vec();
+    }
+    let k = config.optimal_distance.max(1.0);
+    let side = k * (n as f32).sqrt();
+    let (cx, cy) = centroid(input.positions);
+    let mut seed = config.seed;
+    let mut positions: Vec<(f32, f32)> = (0..n)
+        .map(|i| {
+            let x = cx + (rand_f32(&mut seed) - 0.5) * side;
+            let y = cy + (rand_f32(&mut seed) - 0.5) * side;
+            if input.is_pinned(i) {
+                input.positions[i]
+            } else {
+                (x, y)
+            }
+        })
+        .collect();
+
+    let neighbors = adjacency(n, input.edges);
+    let t0 = config.initial_temperature.unwrap_or(side / 10.0);
+    let k2 = k * k;
+    let mut dx = vec![0.0f32; n];
+    let mut dy = vec![0.0f32; n];
+    for iteration in 0..config.iterations {
+        dx.iter_mut().for_each(|d| *d = 0.0);
+        dy.iter_mut().for_each(|d| *d = 0.0);
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let ddx = positions[i].0 - positions[j].0;
+                let ddy = positions[i].1 - positions[j].1;
+                let d2 = (ddx * ddx + ddy * ddy).max(0.01);
+                // k^2 / d along the unit vector
+                let f = k2 / d2;
+                dx[i] += ddx * f;
+                dy[i] += ddy * f;
+                dx[j] -= ddx * f;
+                dy[j] -= ddy * f;
+            }
+        }
+        for (i, adjacent) in neighbors.iter().enumerate() {
+            for &j in adjacent {
+                let ddx = positions[i].0 - positions[j].0;
+                let ddy = positions[i].1 - positions[j].1;
+                // d^2 / k along the unit vector
+                let f = (ddx * ddx + ddy * ddy).sqrt() / k;
+                dx[i] -= ddx * f;
+                dy[i] -= ddy * f;
+            }
+        }
+
+        let temperature = t0 * (1.0 - iteration as f32 / config.iterations as f32);
+        for i in 0..n {
+            if input.is_pinned(i) {
+                continue;
+            }
+            let d = (dx[i] * dx[i] + dy[i] * dy[i]).sqrt();
+            if d > 0.0 {
+                let step = d.min(temperature);
+                positions[i].0 += dx[i] / d * step;
+                positions[i].1 += dy[i] / d * step;
+            }
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    // A path of four nodes and a separate triangle, every node starting at
+    // the same spot except the pinned one
+    fn two_components() -> (Vec<(f32, f32)>, Vec<GpugEdge>, Vec<bool>) {
+        let mut positions = vec![(100.0, 100.0); 7];
+        positions[1] = (300.0, -40.0);
+        let edges = [(0, 1), (1, 2), (2, 3), (4, 5), (5, 6), (6, 4)]
+            .iter()
+            .map(|&(i, j)| GpugEdge::new(i, j))
+            .collect();
+        let mut pinned = vec![false; 7];
+        pinned[1] = true;
+        (positions, edges, pinned)
+    }
+
+    #[test]
+    fn pinned_nodes_keep_their_position_and_components_stay_finite() {
+        let (positions, edges, pinned) = two_components();
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &pinned,
+            radii: &[],
+        };
+        let result = fruchterman_reingold(&input, &FruchtermanReingoldConfig::default());
+        assert_eq!(result.len(), positions.len());
+        assert_eq!(result[1], positions[1]);
+        assert!(result.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+    }
+}
@@ -0,0 +1,325 @@
+use std::f32::consts::{FRAC_PI_4, PI};
+use std::sync::OnceLock;
+
+use crate::layout::{adjacency, Layout, LayoutInput};
+
+// Web Mercator is cut off at the latitude that makes the map square
+const MERCATOR_MAX_LATITUDE: f32 = 85.051_13;
+// How far nodes without a location sit from the neighbours they are placed by
+const NEIGHBOR_OFFSET: f32 = 12.0;
+// Gap between the bottom of the map and the rows of nodes that cannot be placed
+const UNPLACED_MARGIN: f32 = 40.0;
+const UNPLACED_SPACING: f32 = 30.0;
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+// How latitude and longitude map onto the plane
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Projection {
+    // Conformal, as used by web map tiles; stretches areas towards the poles
+    #[default]
+    WebMercator,
+    // Longitude and latitude mapped linearly, giving a map twice as wide as tall
+    Equirectangular,
+}
+
+impl Projection {
+    // Position on a map one unit wide with its origin at the top-left corner
+    pub fn project(self, latitude: f32, longitude: f32) -> (f32, f32) {
+        let x = (longitude + 180.0) / 360.0;
+        let y = match self {
+            Projection::WebMercator => {
+                let phi = latitude
+                    .clamp(-MERCATOR_MAX_LATITUDE, MERCATOR_MAX_LATITUDE)
+                    .to_radians();
+                (1.0 - (FRAC_PI_4 + phi / 2.0).tan().ln() / PI) / 2.0
+            }
+            Projection::Equirectangular => (90.0 - latitude.clamp(-90.0, 90.0)) / 360.0,
+        };
+        (x, y)
+    }
+
+    // Height of a map one unit wide
+    pub fn aspect_ratio(self) -> f32 {
+        match self {
+            Projection::WebMercator => 1.0,
+            Projection::Equirectangular => 0.5,
+        }
+    }
+}
+
+// Where the projected map lies on the canvas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapFrame {
+    pub projection: Projection,
+    // Width of the whole map, 360 degrees of longitude
+    pub width: f32,
+    // Canvas position of the map's top-left corner
+    pub origin: (f32, f32),
+}
+
+impl Default for MapFrame {
+    fn default() -> Self {
+        Self {
+            projection: Projection::WebMercator,
+            width: 2048.0,
+            origin: (0.0, 0.0),
+        }
+    }
+}
+
+impl MapFrame {
+    pub fn project(&self, latitude: f32, longitude: f32) -> (f32, f32) {
+        let (x, y) = self.projection.project(latitude, longitude);
+        (
+            self.origin.0 + x * self.width,
+            self.origin.1 + y * self.width,
+        )
+    }
+
+    pub fn height(&self) -> f32 {
+        self.width * self.projection.aspect_ratio()
+    }
+}
+
+// Parameters of the geographic layout
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeographicConfig {
+    // (latitude, longitude) of each node in degrees, e.g. from
+    // `parse_coordinates`; nodes without one are placed by their neighbours
+    pub coordinates: Vec<Option<(f32, f32)>>,
+    pub frame: MapFrame,
+}
+
+// Each node at its projected location on a map. Results are in the map's
+// frame, so callers must not recentre them.
+#[derive(Default)]
+pub struct GeographicLayout {
+    pub config: GeographicConfig,
+}
+
+impl GeographicLayout {
+    pub fn new(config: GeographicConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for GeographicLayout {
+    fn name(&self) -> &str {
+        "geographic"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        geographic_layout(input, &self.config)
+    }
+
+    fn is_anchored(&self) -> bool {
+        true
+    }
+}
+
+// Located nodes are centred on their projected coordinates. The rest are
+// placed ring by ring outwards from them, each around the mean of its already
+// placed neighbours; components without any location are lined up below the map.
+pub fn geographic_layout(input: &LayoutInput, config: &GeographicConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    let frame = &config.frame;
+    let mut positions: Vec<Option<(f32, f32)>> = (0..n)
+        .map(|i| {
+            let (latitude, longitude) = config.coordinates.get(i).copied().flatten()?;
+            Some(frame.project(latitude, longitude))
+        })
+        .collect();
+
+    let neighbors = adjacency(n, input.edges);
+    let mut seen: Vec<bool> = positions.iter().map(Option::is_some).collect();
+    let mut frontier: Vec<usize> = (0..n).filter(|&i| seen[i]).collect();
+    while !frontier.is_empty() {
+        let mut ring = Vec::new();
+        for &u in &frontier {
+            for &v in &neighbors[u] {
+                if !seen[v] {
+                    seen[v] = true;
+                    ring.push(v);
+                }
+            }
+        }
+        let placed: Vec<(f32, f32)> = ring
+            .iter()
+            .enumerate()
+            .map(|(k, &v)| {
+                let (mut sx, mut sy, mut count) = (0.0f32, 0.0f32, 0.0f32);
+                for &u in &neighbors[v] {
+                    if let Some((x, y)) = positions[u] {
+                        sx += x;
+                        sy += y;
+                        count += 1.0;
+                    }
+                }
+                // Fan nodes out so those sharing neighbours do not coincide
+                let angle = k as f32 * GOLDEN_ANGLE;
+                (
+                    sx / count + NEIGHBOR_OFFSET * angle.cos(),
+                    sy / count + NEIGHBOR_OFFSET * angle.sin(),
+                )
+            })
+            .collect();
+        for (&v, p) in ring.iter().zip(placed) {
+            positions[v] = Some(p);
+        }
+        frontier = ring;
+    }
+
+    let top = frame.origin.1 + frame.height() + UNPLACED_MARGIN;
+    let per_row = ((frame.width / UNPLACED_SPACING) as usize).max(1);
+    let mut slot = 0;
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let (x, y) = p.unwrap_or_else(|| {
+                let (row, column) = (slot / per_row, slot % per_row);
+                slot += 1;
+                (
+                    frame.origin.0 + column as f32 * UNPLACED_SPACING,
+                    top + row as f32 * UNPLACED_SPACING,
+                )
+            });
+            // Everything above works on disc centres
+            let r = input.radius(i);
+            (x - r, y - r)
+        })
+        .collect()
+}
+
+// Pair up per-node latitude and longitude values in degrees, e.g. from
+// `Graph::node_attributes`. Values that do not parse or lie off the globe
+// leave the node without a location.
+pub fn parse_coordinates(
+    latitudes: &[Option<String>],
+    longitudes: &[Option<String>],
+) -> Vec<Option<(f32, f32)>> {
+    latitudes
+        .iter()
+        .zip(longitudes)
+        .map(|(latitude, longitude)| {
+            let latitude: f32 = latitude.as_deref()?.trim().parse().ok()?;
+            let longitude: f32 = longitude.as_deref()?.trim().parse().ok()?;
+            ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+                .then_some((latitude, longitude))
+        })
+        .collect()
+}
+
+// Coarse world coastline bundled with the crate, as closed rings of
+// (latitude, longitude) in degrees
+pub fn coastline() -> &'static [Vec<(f32, f32)>] {
+    static RINGS: OnceLock<Vec<Vec<(f32, f32)>>> = OnceLock::new();
+    RINGS.get_or_init(|| parse_rings(include_str!("../../assets/coastline.txt")))
+}
+
+// One ring per line of space-separated "longitude,latitude" pairs, skipping
+// blank lines, '#' comments and pairs that do not parse
+fn parse_rings(text: &str) -> Vec<Vec<(f32, f32)>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.split_whitespace()
+                .filter_map(|pair| {
+                    let (longitude, latitude) = pair.split_once(',')?;
+                    Some((latitude.parse().ok()?, longitude.parse().ok()?))
+                })
+                .collect::<Vec<(f32, f32)>>()
+        })
+        .filter(|ring| ring.len() >= 2)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near((x, y): (f32, f32), (ex, ey): (f32, f32)) {
+        assert!(
+            (x - ex).abs() < 1e-3 && (y - ey).abs() < 1e-3,
+            "({x}, {y}) instead of ({ex}, {ey})"
+        );
+    }
+
+    #[test]
+    fn coordinates_are_parsed_and_checked() {
+        let values = |v: &[Option<&str>]| -> Vec<Option<String>> {
+            v.iter().map(|s| s.map(str::to_string)).collect()
+        };
+        let latitudes = values(&[
+            Some("51.5"),
+            Some(" -33.9 "),
+            Some("north"),
+            Some("91"),
+            None,
+        ]);
+        let longitudes = values(&[
+            Some("-0.12"),
+            Some("151.2"),
+            Some("10"),
+            Some("0"),
+            Some("5"),
+        ]);
+        assert_eq!(
+            parse_coordinates(&latitudes, &longitudes),
+            vec![Some((51.5, -0.12)), Some((-33.9, 151.2)), None, None, None]
+        );
+        let out_of_range = parse_coordinates(&values(&[Some("0")]), &values(&[Some("180.5")]));
+        assert_eq!(out_of_range, vec![None]);
+    }
+
+    #[test]
+    fn projections_of_known_points() {
+        for projection in [Projection::WebMercator, Projection::Equirectangular] {
+            let (x, y) = projection.project(0.0, 0.0);
+            assert_near((x, y / projection.aspect_ratio()), (0.5, 0.5));
+            assert_near(projection.project(0.0, -180.0), (0.0, y));
+            assert_near(projection.project(0.0, 180.0), (1.0, y));
+        }
+        assert_near(Projection::Equirectangular.project(90.0, 0.0), (0.5, 0.0));
+        assert_near(Projection::Equirectangular.project(-90.0, 0.0), (0.5, 0.5));
+        // Mercator is square, cut off at its maximum latitude
+        assert_near(Projection::WebMercator.project(90.0, 0.0), (0.5, 0.0));
+        assert_near(Projection::WebMercator.project(-90.0, 0.0), (0.5, 1.0));
+
+        let frame = MapFrame {
+            projection: Projection::WebMercator,
+            width: 1000.0,
+            origin: (10.0, 20.0),
+        };
+        assert_near(frame.project(0.0, 0.0), (510.0, 520.0));
+    }
+
+    #[test]
+    fn node_centres_sit_on_their_locations() {
+        let positions = vec![(0.0, 0.0); 3];
+        let radii = [4.0, 10.0, 6.0];
+        let edges = [crate::edge::GpugEdge::new(1, 2)];
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &radii,
+        };
+        let config = GeographicConfig {
+            coordinates: vec![Some((0.0, 0.0)), Some((45.0, 90.0)), None],
+            frame: MapFrame::default(),
+        };
+        let result = geographic_layout(&input, &config);
+
+        for i in 0..2 {
+            let (latitude, longitude) = config.coordinates[i].unwrap();
+            let centre = (result[i].0 + radii[i], result[i].1 + radii[i]);
+            assert_near(centre, config.frame.project(latitude, longitude));
+        }
+        // Placed next to its located neighbour
+        let (x, y) = (result[2].0 + radii[2], result[2].1 + radii[2]);
+        let (nx, ny) = config.frame.project(45.0, 90.0);
+        assert!(((x - nx).powi(2) + (y - ny).powi(2)).sqrt() < 2.0 * NEIGHBOR_OFFSET);
+    }
+}
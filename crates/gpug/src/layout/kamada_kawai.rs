@@ -0,0 +1,224 @@
+use std::f32::consts::TAU;
+
+use crate::layout::{centroid, shortest_paths, Layout, LayoutInput};
+
+// Parameters of the Kamada-Kawai layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KamadaKawaiConfig {
+    // Desired length of a single edge; graph distances are multiples of it
+    pub edge_length: f32,
+    // Overall spring stiffness K
+    pub spring_constant: f32,
+    // Stop once every node's energy gradient is below this
+    pub tolerance: f32,
+    // Cap on how many times a node is picked and relaxed
+    pub max_iterations: usize,
+    // Cap on Newton-Raphson steps each time a node is relaxed
+    pub newton_iterations: usize,
+}
+
+impl Default for KamadaKawaiConfig {
+    fn default() -> Self {
+        Self {
+            edge_length: 50.0,
+            spring_constant: 1.0,
+            tolerance: 0.1,
+            max_iterations: 5000,
+            newton_iterations: 50,
+        }
+    }
+}
+
+// Kamada-Kawai (1989): springs between every pair with rest length equal to
+// their graph distance, minimised one node at a time by Newton-Raphson
+#[derive(Default)]
+pub struct KamadaKawaiLayout {
+    pub config: KamadaKawaiConfig,
+}
+
+impl KamadaKawaiLayout {
+    pub fn new(config: KamadaKawaiConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for KamadaKawaiLayout {
+    fn name(&self) -> &str {
+        "kamada-kawai"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        kamada_kawai(input, &self.config)
+    }
+}
+
+// Deterministic: free nodes start on a circle around the input centroid,
+// pinned nodes keep their input position and are never relaxed
+pub fn kamada_kawai(input: &LayoutInput, config: &KamadaKawaiConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n < 2 {
+        return input.positions.to_vec();
+    }
+    let length = config.edge_length.max(1.0);
+
+    // Graph distances in pixels; disconnected pairs sit a hop beyond the
+    // farthest connected pair
+    let mut distances = shortest_paths(n, input.edges, length);
+    let max_finite = distances
+        .iter()
+        .copied()
+        .filter(|d| d.is_finite())
+        .fold(0.0f32, f32::max);
+    for d in distances.iter_mut() {
+        if !d.is_finite() {
+            *d = max_finite + length;
+        }
+    }
+    let dist = |i: usize, j: usize| distances[i * n + j].max(1e-3);
+    // Stiffness falls off with the hop count rather than the pixel distance so
+    // the tolerance means the same thing whatever the edge length
+    let stiffness = |i: usize, j: usize| {
+        let hops = dist(i, j) / length;
+        config.spring_constant / (hops * hops)
+    };
+
+    let (cx, cy) = centroid(input.positions);
+    let radius = max_finite.max(length) * 0.5;
+    let mut positions: Vec<(f32, f32)> = (0..n)
+        .map(|i| {
+            if input.is_pinned(i) {
+                return input.positions[i];
+            }
+            let angle = TAU * i as f32 / n as f32;
+            (cx + radius * angle.cos(), cy + radius * angle.sin())
+        })
+        .collect();
+
+    // Energy gradient contributed to node m by node i
+    let partial = |positions: &[(f32, f32)], m: usize, i: usize| -> (f32, f32) {
+        let dx = positions[m].0 - positions[i].0;
+        let dy = positions[m].1 - positions[i].1;
+        let d = (dx * dx + dy * dy).sqrt().max(1e-3);
+        let k = stiffness(m, i);
+        let l = dist(m, i);
+        (k * (dx - l * dx / d), k * (dy - l * dy / d))
+    };
+
+    let mut gx = vec![0.0f32; n];
+    let mut gy = vec![0.0f32; n];
+    for m in 0..n {
+        for i in 0..n {
+            if i != m {
+                let (px, py) = partial(&positions, m, i);
+                gx[m] += px;
+                gy[m] += py;
+            }
+        }
+    }
+
+    for _ in 0..config.max_iterations {
+        // Relax the free node with the steepest gradient
+        let mut best: Option<(usize, f32)> = None;
+        for m in 0..n {
+            if input.is_pinned(m) {
+                continue;
+            }
+            let delta = (gx[m] * gx[m] + gy[m] * gy[m]).sqrt();
+            if best.is_none_or(|(_, b)| delta > b) {
+                best = Some((m, delta));
+            }
+        }
+        let Some((m, delta)) = best else {
+            break;
+        };
+        if delta < config.tolerance {
+            break;
+        }
+
+        let old = positions[m];
+        for _ in 0..config.newton_iterations {
+            let (mut ex, mut ey) = (0.0f32, 0.0f32);
+            let (mut exx, mut eyy, mut exy) = (0.0f32, 0.0f32, 0.0f32);
+            for i in 0..n {
+                if i == m {
+                    continue;
+                }
+                let dx = positions[m].0 - positions[i].0;
+                let dy = positions[m].1 - positions[i].1;
+                let d = (dx * dx + dy * dy).sqrt().max(1e-3);
+                let d3 = d * d * d;
+                let k = stiffness(m, i);
+                let l = dist(m, i);
+                ex += k * (dx - l * dx / d);
+                ey += k * (dy - l * dy / d);
+                exx += k * (1.0 - l * dy * dy / d3);
+                eyy += k * (1.0 - l * dx * dx / d3);
+                exy += k * l * dx * dy / d3;
+            }
+            if (ex * ex + ey * ey).sqrt() < config.tolerance {
+                break;
+            }
+            let det = exx * eyy - exy * exy;
+            if det.abs() < 1e-9 {
+                break;
+            }
+            positions[m].0 += (-ex * eyy + ey * exy) / det;
+            positions[m].1 += (-ey * exx + ex * exy) / det;
+        }
+
+        // Update every gradient for m's move instead of recomputing all pairs
+        gx[m] = 0.0;
+        gy[m] = 0.0;
+        let moved = positions[m];
+        for i in 0..n {
+            if i == m {
+                continue;
+            }
+            positions[m] = old;
+            let (ox, oy) = partial(&positions, i, m);
+            positions[m] = moved;
+            let (nx, ny) = partial(&positions, i, m);
+            gx[i] += nx - ox;
+            gy[i] += ny - oy;
+            let (px, py) = partial(&positions, m, i);
+            gx[m] += px;
+            gy[m] += py;
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    // A path of four nodes and a separate triangle, every node starting at
+    // the same spot except the pinned one
+    fn two_components() -> (Vec<(f32, f32)>, Vec<GpugEdge>, Vec<bool>) {
+        let mut positions = vec![(100.0, 100.0); 7];
+        positions[1] = (300.0, -40.0);
+        let edges = [(0, 1), (1, 2), (2, 3), (4, 5), (5, 6), (6, 4)]
+            .iter()
+            .map(|&(i, j)| GpugEdge::new(i, j))
+            .collect();
+        let mut pinned = vec![false; 7];
+        pinned[1] = true;
+        (positions, edges, pinned)
+    }
+
+    #[test]
+    fn pinned_nodes_keep_their_position_and_components_stay_finite() {
+        let (positions, edges, pinned) = two_components();
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &pinned,
+            radii: &[],
+        };
+        let result = kamada_kawai(&input, &KamadaKawaiConfig::default());
+        assert_eq!(result.len(), positions.len());
+        assert_eq!(result[1], positions[1]);
+        assert!(result.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::VecDeque;
+
+use crate::layout::{centroid, recenter, Layout, LayoutInput};
+
+// Which way edges point once the layers are stacked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerDirection {
+    TopToBottom,
+    LeftToRight,
+}
+
+// How a node's place within its layer is derived from its neighbours in the
+// adjacent layer during crossing minimisation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossingHeuristic {
+    Barycenter,
+    Median,
+}
+
+// Parameters of the layered (Sugiyama) layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayeredConfig {
+    pub direction: LayerDirection,
+    pub heuristic: CrossingHeuristic,
+    // Distance between consecutive layers
+    pub layer_spacing: f32,
+    // Minimum distance between neighbours within a layer
+    pub node_spacing: f32,
+    // Alternating down/up passes of crossing minimisation
+    pub sweeps: usize,
+    // Passes pulling each node towards its neighbours after ordering
+    pub coordinate_passes: usize,
+}
+
+impl Default for LayeredConfig {
+    fn default() -> Self {
+        Self {
+            direction: LayerDirection::TopToBottom,
+            heuristic: CrossingHeuristic::Barycenter,
+            layer_spacing: 80.0,
+            node_spacing: 40.0,
+            sweeps: 24,
+            coordinate_passes: 8,
+        }
+    }
+}
+
+// Sugiyama-style layout for directed graphs: break cycles, assign layers by
+// longest path, order each layer to reduce crossings and place nodes so edges
+// run as straight as the ordering allows
+#[derive(Default)]
+pub struct LayeredLayout {
+    pub config: LayeredConfig,
+}
+
+impl LayeredLayout {
+    pub fn new(config: LayeredConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for LayeredLayout {
+    fn name(&self) -> &str {
+        "layered"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        layered_layout(input, &self.config)
+    }
+}
+
+// Edges point from `source` to `target`. Pinned nodes are laid out like any
+// other; `Graph::apply_layout` keeps them in place afterwards.
+pub fn layered_layout(input: &LayoutInput, config: &LayeredConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let edges = acyclic_edges(n, input);
+    let layer = longest_path_layers(n, &edges);
+    let mut positions = place_layers(n, &edges, layer, config);
+    recenter(&mut positions, centroid(input.positions));
+    positions
+}
+
+// Stack nodes by `layer`, with every edge (u, v) pointing to a later layer,
+// and order and space each layer as the layered layout does. Positions start
+// at the origin.
+pub fn place_layers(
+    n: usize,
+    edges: &[(usize, usize)],
+    layer: Vec<usize>,
+    config: &LayeredConfig,
+) -> Vec<(f32, f32)> {
+    // Split edges spanning several layers with dummy nodes so every edge
+    // joins adjacent layers; dummies are indices n.. and are dropped at the end
+    let mut layer_of = layer;
+    let mut up: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut down: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for &(u, v) in edges {
+        let mut previous = u;
+        for l in layer_of[u] + 1..layer_of[v] {
+            let dummy = layer_of.len();
+            layer_of.push(l);
+            up.push(vec![previous]);
+            down.push(Vec::new());
+            down[previous].push(dummy);
+            previous = dummy;
+        }
+        down[previous].push(v);
+        up[v].push(previous);
+    }
+
+    let layer_count = layer_of.iter().copied().max().unwrap_or(0) + 1;
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (v, &l) in layer_of.iter().enumerate() {
+        layers[l].push(v);
+    }
+
+    order_layers(&mut layers, &up, &down, config);
+    let along = assign_coordinates(&layers, &up, &down, config);
+
+    (0..n)
+        .map(|v| {
+            let across = layer_of[v] as f32 * config.layer_spacing;
+            match config.direction {
+                LayerDirection::TopToBottom => (along[v], across),
+                LayerDirection::LeftToRight => (across, along[v]),
+            }
+        })
+        .collect()
+}
+
+// Deduplicated directed edges with self-loops dropped and every edge that
+// closes a cycle in a depth-first search reversed
+fn acyclic_edges(n: usize, input: &LayoutInput) -> Vec<(usize, usize)> {
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for edge in input.edges {
+        let (u, v) = (edge.source, edge.target);
+        if u < n && v < n && u != v {
+            out[u].push(v);
+        }
+    }
+
+    // 0 = unvisited, 1 = on the DFS stack, 2 = finished
+    let mut state = vec![0u8; n];
+    let mut edges: Vec<(usize, usize)> = Vec::new();
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        stack.push((root, 0));
+        while let Some((u, next)) = stack.last_mut() {
+            let u = *u;
+            if let Some(&v) = out[u].get(*next) {
+                *next += 1;
+                match state[v] {
+                    0 => {
+                        edges.push((u, v));
+                        state[v] = 1;
+                        stack.push((v, 0));
+                    }
+                    1 => edges.push((v, u)),
+                    _ => edges.push((u, v)),
+                }
+            } else {
+                state[u] = 2;
+                stack.pop();
+            }
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+// Longest-path layering: sources on layer 0, every other node one layer
+// below its deepest predecessor
+fn longest_path_layers(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut indegree = vec![0usize; n];
+    for &(u, v) in edges {
+        out[u].push(v);
+        indegree[v] += 1;
+    }
+    let mut layer = vec![0usize; n];
+    let mut queue: VecDeque<usize> = (0..n).filter(|&v| indegree[v] == 0).collect();
+    while let Some(u) = queue.pop_front() {
+        for &v in &out[u] {
+            layer[v] = layer[v].max(layer[u] + 1);
+            indegree[v] -= 1;
+            if indegree[v] == 0 {
+                queue.push_back(v);
+            }
+        }
+    }
+    layer
+}
+
+// Layer-by-layer sweeps sorting each layer by the barycenter or median of its
+// neighbours in the previous layer, keeping the ordering with fewest crossings
+fn order_layers(
+    layers: &mut [Vec<usize>],
+    up: &[Vec<usize>],
+    down: &[Vec<usize>],
+    config: &LayeredConfig,
+) {
+    let mut position = vec![0usize; up.len()];
+    for layer in layers.iter() {
+        for (i, &v) in layer.iter().enumerate() {
+            position[v] = i;
+        }
+    }
+
+    let mut best = layers.to_vec();
+    let mut best_crossings = count_crossings(layers, down, &position);
+    for sweep in 0..config.sweeps {
+        if best_crossings == 0 {
+            break;
+        }
+        let downward = sweep % 2 == 0;
+        let order: Vec<usize> = if downward {
+            (1..layers.len()).collect()
+        } else {
+            (0..layers.len().saturating_sub(1)).rev().collect()
+        };
+        for l in order {
+            let fixed = if downward { up } else { down };
+            let mut keyed: Vec<(f32, usize)> = layers[l]
+                .iter()
+                .map(|&v| {
+                    let mut adjacent: Vec<f32> =
+                        fixed[v].iter().map(|&u| position[u] as f32).collect();
+                    (
+                        ordering_key(&mut adjacent, config.heuristic, position[v] as f32),
+                        v,
+                    )
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[l] = keyed.into_iter().map(|(_, v)| v).collect();
+            for (i, &v) in layers[l].iter().enumerate() {
+                position[v] = i;
+            }
+        }
+        let crossings = count_crossings(layers, down, &position);
+        if crossings < best_crossings {
+            best_crossings = crossings;
+            best = layers.to_vec();
+        }
+    }
+    layers.clone_from_slice(&best);
+}
+
+// Sort key of a node from its neighbours' positions; nodes without neighbours
+// in the fixed layer keep their current place
+fn ordering_key(adjacent: &mut [f32], heuristic: CrossingHeuristic, current: f32) -> f32 {
+    if adjacent.is_empty() {
+        return current;
+    }
+    match heuristic {
+        CrossingHeuristic::Barycenter => adjacent.iter().sum::<f32>() / adjacent.len() as f32,
+        CrossingHeuristic::Median => {
+            adjacent.sort_by(f32::total_cmp);
+            let mid = adjacent.len() / 2;
+            if adjacent.len() % 2 == 1 {
+                adjacent[mid]
+            } else {
+                (adjacent[mid - 1] + adjacent[mid]) * 0.5
+            }
+        }
+    }
+}
+
+// Total edge crossings between all pairs of adjacent layers, counted as
+// inversions of the lower endpoints with a Fenwick tree
+fn count_crossings(layers: &[Vec<usize>], down: &[Vec<usize>], position: &[usize]) -> usize {
+    let mut total = 0;
+    for pair in layers.windows(2) {
+        let width = pair[1].len();
+        let mut tree = vec![0usize; width + 1];
+        let mut seen = 0;
+        for &u in &pair[0] {
+            let mut targets: Vec<usize> = down[u].iter().map(|&v| position[v]).collect();
+            targets.sort_unstable();
+            for &t in &targets {
+                // Edges already seen ending strictly right of t cross this one
+                let mut at_most = 0;
+                let mut i = t + 1;
+                while i > 0 {
+                    at_most += tree[i];
+                    i -= i & i.wrapping_neg();
+                }
+                total += seen - at_most;
+                let mut i = t + 1;
+                while i <= width {
+                    tree[i] += 1;
+                    i += i & i.wrapping_neg();
+                }
+                seen += 1;
+            }
+        }
+    }
+    total
+}
+
+// Position of every node (including dummies) along its layer: start evenly
+// spaced, then repeatedly move each layer towards the mean of its neighbours
+// while keeping the order and `node_spacing`
+fn assign_coordinates(
+    layers: &[Vec<usize>],
+    up: &[Vec<usize>],
+    down: &[Vec<usize>],
+    config: &LayeredConfig,
+) -> Vec<f32> {
+    let spacing = config.node_spacing.max(1.0);
+    let mut along = vec![0.0f32; up.len()];
+    let widest = layers.iter().map(Vec::len).max().unwrap_or(0) as f32;
+    for layer in layers {
+        let offset = (widest - layer.len() as f32) * spacing * 0.5;
+        for (i, &v) in layer.iter().enumerate() {
+            along[v] = offset + i as f32 * spacing;
+        }
+    }
+
+    for pass in 0..config.coordinate_passes {
+        let order: Vec<usize> = if pass % 2 == 0 {
+            (0..layers.len()).collect()
+        } else {
+            (0..layers.len()).rev().collect()
+        };
+        for l in order {
+            let targets: Vec<f32> = layers[l]
+                .iter()
+                .map(|&v| {
+                    let adjacent = up[v].iter().chain(&down[v]);
+                    let count = up[v].len() + down[v].len();
+                    if count == 0 {
+                        along[v]
+                    } else {
+                        adjacent.map(|&u| along[u]).sum::<f32>() / count as f32
+                    }
+                })
+                .collect();
+            for (&v, x) in layers[l].iter().zip(closest_spaced(&targets, spacing)) {
+                along[v] = x;
+            }
+        }
+    }
+    along
+}
+
+// Positions as close as possible (least squares) to `targets` that keep their
+// order and stay at least `spacing` apart, via pool-adjacent-violators on the
+// targets shifted by i * spacing
+fn closest_spaced(targets: &[f32], spacing: f32) -> Vec<f32> {
+    // Blocks of (sum, count) whose shared value is their mean
+    let mut blocks: Vec<(f32, usize)> = Vec::with_capacity(targets.len());
+    for (i, &t) in targets.iter().enumerate() {
+        blocks.push((t - i as f32 * spacing, 1));
+        while blocks.len() > 1 {
+            let (sum, count) = blocks[blocks.len() - 1];
+            let (prev_sum, prev_count) = blocks[blocks.len() - 2];
+            if prev_sum / prev_count as f32 <= sum / count as f32 {
+                break;
+            }
+            blocks.pop();
+            let last = blocks.len() - 1;
+            blocks[last] = (prev_sum + sum, prev_count + count);
+        }
+    }
+    let mut result = Vec::with_capacity(targets.len());
+    for (sum, count) in blocks {
+        let value = sum / count as f32;
+        for _ in 0..count {
+            result.push(value + result.len() as f32 * spacing);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    fn input_for<'a>(positions: &'a [(f32, f32)], edges: &'a [GpugEdge]) -> LayoutInput<'a> {
+        LayoutInput {
+            positions,
+            edges,
+            pinned: &[],
+            radii: &[],
+        }
+    }
+
+    #[test]
+    fn cycles_are_broken() {
+        let edges: Vec<GpugEdge> = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 1)]
+            .iter()
+            .map(|&(u, v)| GpugEdge::new(u, v))
+            .collect();
+        let positions = vec![(0.0, 0.0); 4];
+        let dag = acyclic_edges(4, &input_for(&positions, &edges));
+
+        // Every edge survives in one direction or the other
+        for edge in &edges {
+            let (u, v) = (edge.source, edge.target);
+            assert!(dag.contains(&(u, v)) || dag.contains(&(v, u)));
+        }
+        // and a topological order exists
+        let mut indegree = [0usize; 4];
+        for &(_, v) in &dag {
+            indegree[v] += 1;
+        }
+        let mut ready: Vec<usize> = (0..4).filter(|&v| indegree[v] == 0).collect();
+        let mut visited = 0;
+        while let Some(u) = ready.pop() {
+            visited += 1;
+            for &(_, v) in dag.iter().filter(|&&(s, _)| s == u) {
+                indegree[v] -= 1;
+                if indegree[v] == 0 {
+                    ready.push(v);
+                }
+            }
+        }
+        assert_eq!(visited, 4);
+    }
+
+    #[test]
+    fn crossings_of_a_known_drawing() {
+        // 0 1 2 over 3 4 5 with 0-5, 1-4 and 2-3 all crossing each other,
+        // while 0-3 crosses nothing: it runs left of 1-4 and shares an
+        // endpoint with 2-3
+        let layers = vec![vec![0, 1, 2], vec![3, 4, 5]];
+        let mut down = vec![Vec::new(); 6];
+        down[0] = vec![5, 3];
+        down[1] = vec![4];
+        down[2] = vec![3];
+        let position = [0, 1, 2, 0, 1, 2];
+        assert_eq!(count_crossings(&layers, &down, &position), 3);
+
+        down[0] = vec![3];
+        down[1] = vec![4];
+        down[2] = vec![5];
+        assert_eq!(count_crossings(&layers, &down, &position), 0);
+    }
+
+    #[test]
+    fn edges_point_down_on_a_dag() {
+        let edges: Vec<GpugEdge> = [(0, 1), (0, 2), (1, 3), (2, 3), (0, 3), (3, 4), (1, 4)]
+            .iter()
+            .map(|&(u, v)| GpugEdge::new(u, v))
+            .collect();
+        let positions = vec![(0.0, 0.0); 5];
+        let result = layered_layout(&input_for(&positions, &edges), &LayeredConfig::default());
+        for edge in &edges {
+            assert!(
+                result[edge.target].1 > result[edge.source].1,
+                "{} -> {}",
+                edge.source,
+                edge.target
+            );
+        }
+    }
+}
@@ -0,0 +1,248 @@
+pub mod circular;
+pub mod fruchterman_reingold;
+pub mod geographic;
+pub mod kamada_kawai;
+pub mod layered;
+pub mod multilevel;
+pub mod multipartite;
+pub mod orthogonal;
+pub mod shell;
+pub mod spectral;
+pub mod stress;
+pub mod tree;
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::edge::GpugEdge;
+use crate::node::GpugNode;
+
+// Graph handed to a layout: current positions (top-left corners, as in the
+// force simulation), edges and which nodes must keep their position
+pub struct LayoutInput<'a> {
+    pub positions: &'a [(f32, f32)],
+    pub edges: &'a [GpugEdge],
+    pub pinned: &'a [bool],
+    // Disc radius of each node, so constraints can act on centres; missing
+    // radii count as zero
+    pub radii: &'a [f32],
+}
+
+impl LayoutInput<'_> {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn is_pinned(&self, index: usize) -> bool {
+        self.pinned.get(index).copied().unwrap_or(false)
+    }
+
+    pub fn radius(&self, index: usize) -> f32 {
+        self.radii.get(index).copied().unwrap_or(0.0)
+    }
+}
+
+// A one-shot layout computing a new position for every node
+pub trait Layout {
+    fn name(&self) -> &str;
+
+    // Returns one position per input node
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)>;
+
+    // Spacing of the grid results are aligned to, so callers moving them
+    // around keep them on it
+    fn grid_spacing(&self) -> Option<f32> {
+        None
+    }
+
+    // Whether results sit in a fixed frame, such as a map projection, that
+    // callers must not move
+    fn is_anchored(&self) -> bool {
+        false
+    }
+}
+
+// Lay out nodes before they are handed to `Graph::new`; pinned nodes keep
+// their position and everything else moves to the layout's result
+pub fn layout_nodes(nodes: &mut [GpugNode], edges: &[GpugEdge], layout: &mut dyn Layout) {
+    let positions: Vec<(f32, f32)> = nodes.iter().map(GpugNode::position).collect();
+    let pinned: Vec<bool> = nodes.iter().map(|n| n.pinned).collect();
+    let radii: Vec<f32> = nodes.iter().map(|n| n.radius).collect();
+    let result = layout.layout(&LayoutInput {
+        positions: &positions,
+        edges,
+        pinned: &pinned,
+        radii: &radii,
+    });
+    for (node, &(x, y)) in nodes.iter_mut().zip(&result) {
+        if !node.pinned {
+            node.set_position(x, y);
+        }
+    }
+}
+
+// Undirected adjacency lists, skipping self-loops and out-of-range edges
+pub fn adjacency(n: usize, edges: &[GpugEdge]) -> Vec<Vec<usize>> {
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for edge in edges {
+        let (i, j) = (edge.source, edge.target);
+        if i >= n || j >= n || i == j {
+            continue;
+        }
+        neighbors[i].push(j);
+        neighbors[j].push(i);
+    }
+    neighbors
+}
+
+pub fn centroid(positions: &[(f32, f32)]) -> (f32, f32) {
+    if positions.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = positions.len() as f32;
+    let (sx, sy) = positions
+        .iter()
+        .fold((0.0f32, 0.0f32), |(sx, sy), &(x, y)| (sx + x, sy + y));
+    (sx / n, sy / n)
+}
+
+// Dense rank of each node's attribute value: numeric when every present value
+// parses as a number, lexicographic otherwise, with missing values last
+pub fn attribute_ranks(values: &[Option<String>]) -> Vec<usize> {
+    let numeric = values
+        .iter()
+        .flatten()
+        .all(|v| v.trim().parse::<f64>().is_ok());
+    let mut distinct: Vec<&str> = values.iter().flatten().map(String::as_str).collect();
+    if numeric {
+        distinct.sort_by(|a, b| {
+            let x: f64 = a.trim().parse().unwrap_or(0.0);
+            let y: f64 = b.trim().parse().unwrap_or(0.0);
+            x.total_cmp(&y).then_with(|| a.cmp(b))
+        });
+    } else {
+        distinct.sort_unstable();
+    }
+    distinct.dedup();
+    let rank: HashMap<&str, usize> = distinct.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    values
+        .iter()
+        .map(|v| match v {
+            Some(v) => rank[v.as_str()],
+            None => distinct.len(),
+        })
+        .collect()
+}
+
+// Translate positions so their centroid lands on `center`
+pub fn recenter(positions: &mut [(f32, f32)], center: (f32, f32)) {
+    recenter_on_grid(positions, center, None);
+}
+
+// As `recenter`, but moving by whole multiples of `grid_spacing` when given so
+// positions aligned to the grid stay aligned
+pub fn recenter_on_grid(
+    positions: &mut [(f32, f32)],
+    center: (f32, f32),
+    grid_spacing: Option<f32>,
+) {
+    let (cx, cy) = centroid(positions);
+    let (mut dx, mut dy) = (center.0 - cx, center.1 - cy);
+    if let Some(spacing) = grid_spacing.filter(|&s| s > 0.0) {
+        dx = snap_to_grid(dx, spacing);
+        dy = snap_to_grid(dy, spacing);
+    }
+    for p in positions.iter_mut() {
+        p.0 += dx;
+        p.1 += dy;
+    }
+}
+
+// Nearest multiple of `spacing`
+pub fn snap_to_grid(value: f32, spacing: f32) -> f32 {
+    (value / spacing).round() * spacing
+}
+
+// Weighted undirected adjacency lists where each edge counts
+// `GpugEdge::length` or `default_length`
+pub fn weighted_adjacency(
+    n: usize,
+    edges: &[GpugEdge],
+    default_length: f32,
+) -> Vec<Vec<(usize, f32)>> {
+    let mut neighbors: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+    for edge in edges {
+        let (i, j) = (edge.source, edge.target);
+        if i >= n || j >= n || i == j {
+            continue;
+        }
+        let length = edge.length.unwrap_or(default_length).max(0.0);
+        neighbors[i].push((j, length));
+        neighbors[j].push((i, length));
+    }
+    neighbors
+}
+
+// Single-source shortest path lengths written into `row`, which must hold
+// one entry per node; unreachable nodes are infinite
+pub fn shortest_paths_from(neighbors: &[Vec<(usize, f32)>], source: usize, row: &mut [f32]) {
+    row.fill(f32::INFINITY);
+    row[source] = 0.0;
+    let mut heap = BinaryHeap::new();
+    heap.push(Visit(0.0, source));
+    while let Some(Visit(d, u)) = heap.pop() {
+        if d > row[u] {
+            continue;
+        }
+        for &(v, length) in &neighbors[u] {
+            let next = d + length;
+            if next < row[v] {
+                row[v] = next;
+                heap.push(Visit(next, v));
+            }
+        }
+    }
+}
+
+// All-pairs shortest path lengths as a flat n * n matrix, row-major. Each
+// edge counts `GpugEdge::length` or `default_length`; unreachable pairs are
+// infinite.
+pub fn shortest_paths(n: usize, edges: &[GpugEdge], default_length: f32) -> Vec<f32> {
+    let neighbors = weighted_adjacency(n, edges, default_length);
+    let mut distances = vec![f32::INFINITY; n * n];
+    for source in 0..n {
+        shortest_paths_from(
+            &neighbors,
+            source,
+            &mut distances[source * n..(source + 1) * n],
+        );
+    }
+    distances
+}
+
+// Min-heap entry for Dijkstra ordered by distance
+struct Visit(f32, usize);
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0).then(other.1.cmp(&self.1))
+    }
+}
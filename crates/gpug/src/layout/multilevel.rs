@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use crate::generators::utils::rand_f32;
+use crate::layout::{centroid, recenter, Layout, LayoutInput};
+use crate::simulation::quadtree::QuadTree;
+
+// Parameters of the multilevel spring-electrical layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MultilevelConfig {
+    // Natural edge length K of the spring-electrical model
+    pub natural_length: f32,
+    // Strength C of repulsion relative to the springs
+    pub repulsion: f32,
+    // Pull toward the centroid, which keeps disconnected parts and nodes with
+    // nothing but pinned neighbours from drifting away
+    pub gravity: f32,
+    // Stop coarsening once a level has at most this many nodes
+    pub coarsest_size: usize,
+    pub max_levels: usize,
+    // Upper bound on refinement iterations per level
+    pub iterations: usize,
+    // Barnes-Hut opening angle for repulsion
+    pub theta: f32,
+    // A level is refined once the step shrinks below tolerance * natural_length
+    pub tolerance: f32,
+    pub seed: u64,
+}
+
+impl Default for MultilevelConfig {
+    fn default() -> Self {
+        Self {
+            natural_length: 40.0,
+            repulsion: 0.2,
+            gravity: 0.1,
+            coarsest_size: 50,
+            max_levels: 20,
+            iterations: 300,
+            theta: 0.9,
+            tolerance: 0.01,
+            seed: 0x5EED_0F6A_11E5_C0DE,
+        }
+    }
+}
+
+// Multilevel layout in the spirit of FM3/sfdp: coarsen the graph by edge
+// matching, lay out the coarsest graph, then prolong and refine level by level
+#[derive(Default)]
+pub struct MultilevelLayout {
+    pub config: MultilevelConfig,
+}
+
+impl MultilevelLayout {
+    pub fn new(config: MultilevelConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for MultilevelLayout {
+    fn name(&self) -> &str {
+        "multilevel"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        multilevel_layout(input, &self.config)
+    }
+}
+
+// Weighted graph at one level of the hierarchy
+struct Level {
+    masses: Vec<f32>,
+    edges: Vec<(usize, usize, f32)>,
+}
+
+impl Level {
+    fn len(&self) -> usize {
+        self.masses.len()
+    }
+}
+
+// Lay out `input` with the multilevel scheme. Pinned nodes keep their input
+// position and the result is centred on the input's centroid.
+pub fn multilevel_layout(input: &LayoutInput, config: &MultilevelConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n < 2 {
+        return input.positions.to_vec();
+    }
+    let mut seed = config.seed;
+    let k = config.natural_length.max(1.0);
+
+    // Finest level: the input graph with duplicate edges merged
+    let mut merged: HashMap<(usize, usize), f32> = HashMap::with_capacity(input.edges.len());
+    for edge in input.edges {
+        let (i, j) = (edge.source, edge.target);
+        if i >= n || j >= n || i == j {
+            continue;
+        }
+        merged.insert((i.min(j), i.max(j)), 1.0);
+    }
+    let mut levels = vec![Level {
+        masses: vec![1.0; n],
+        edges: merged.into_iter().map(|((i, j), w)| (i, j, w)).collect(),
+    }];
+    // parents[l][i] is the node of level l + 1 that node i of level l merged into
+    let mut parents: Vec<Vec<usize>> = Vec::new();
+
+    while levels.len() < config.max_levels {
+        let finest = &levels[levels.len() - 1];
+        if finest.len() <= config.coarsest_size.max(2) {
+            break;
+        }
+        let Some((parent, coarse)) = coarsen(finest, &mut seed) else {
+            break;
+        };
+        parents.push(parent);
+        levels.push(coarse);
+    }
+
+    // Random start for the coarsest level, sized for its node count
+    let coarsest = &levels[levels.len() - 1];
+    let side = k * (coarsest.len() as f32).sqrt();
+    let mut positions: Vec<(f32, f32)> = (0..coarsest.len())
+        .map(|_| (rand_f32(&mut seed) * side, rand_f32(&mut seed) * side))
+        .collect();
+    let free = vec![false; coarsest.len()];
+    refine(&mut positions, coarsest, &free, config, k);
+
+    // Prolong each layout onto the next finer level and refine it there
+    for level in (0..levels.len() - 1).rev() {
+        let parent = &parents[level];
+        positions = parent
+            .iter()
+            .map(|&p| {
+                let (x, y) = positions[p];
+                let jx = (rand_f32(&mut seed) - 0.5) * k * 0.1;
+                let jy = (rand_f32(&mut seed) - 0.5) * k * 0.1;
+                (x + jx, y + jy)
+            })
+            .collect();
+        let free = vec![false; levels[level].len()];
+        refine(&mut positions, &levels[level], &free, config, k * 0.5);
+    }
+
+    let pinned: Vec<usize> = (0..n).filter(|&i| input.is_pinned(i)).collect();
+    if pinned.is_empty() {
+        // The spring-electrical model fixes shape but not scale, so scale the
+        // result until edges average the natural length
+        let finest = &levels[0];
+        if !finest.edges.is_empty() {
+            let total: f32 = finest
+                .edges
+                .iter()
+                .map(|&(i, j, _)| {
+                    let dx = positions[j].0 - positions[i].0;
+                    let dy = positions[j].1 - positions[i].1;
+                    (dx * dx + dy * dy).sqrt()
+                })
+                .sum();
+            let mean = total / finest.edges.len() as f32;
+            if mean > 0.0 {
+                let scale = k / mean;
+                for p in positions.iter_mut() {
+                    p.0 *= scale;
+                    p.1 *= scale;
+                }
+            }
+        }
+        recenter(&mut positions, centroid(input.positions));
+        return positions;
+    }
+
+    // Move the layout onto the pinned nodes, snap them in place and let the
+    // rest settle around them
+    let target: Vec<(f32, f32)> = pinned.iter().map(|&i| input.positions[i]).collect();
+    let current: Vec<(f32, f32)> = pinned.iter().map(|&i| positions[i]).collect();
+    let (tx, ty) = centroid(&target);
+    let (cx, cy) = centroid(&current);
+    for p in positions.iter_mut() {
+        p.0 += tx - cx;
+        p.1 += ty - cy;
+    }
+    for &i in &pinned {
+        positions[i] = input.positions[i];
+    }
+    let fixed: Vec<bool> = (0..n).map(|i| input.is_pinned(i)).collect();
+    refine(&mut positions, &levels[0], &fixed, config, k * 0.5);
+    positions
+}
+
+// Contract a level by a randomised matching that prefers heavy edges between
+// light nodes; returns `None` once the graph stops shrinking meaningfully
+fn coarsen(level: &Level, seed: &mut u64) -> Option<(Vec<usize>, Level)> {
+    let n = level.len();
+    let mut neighbors: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+    for &(i, j, w) in &level.edges {
+        neighbors[i].push((j, w));
+        neighbors[j].push((i, w));
+    }
+
+    // Visit nodes in a shuffled order so the matching is not biased by index
+    let mut order: Vec<usize> = (0..n).collect();
+    for i in (1..n).rev() {
+        let j = ((rand_f32(seed) * (i + 1) as f32) as usize).min(i);
+        order.swap(i, j);
+    }
+
+    let unassigned = usize::MAX;
+    let mut parent = vec![unassigned; n];
+    let mut coarse_n = 0;
+    for &u in &order {
+        if parent[u] != unassigned {
+            continue;
+        }
+        let mut best: Option<(usize, f32)> = None;
+        for &(v, w) in &neighbors[u] {
+            if parent[v] != unassigned {
+                continue;
+            }
+            let score = w / (level.masses[u] + level.masses[v]);
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((v, score));
+            }
+        }
+        parent[u] = coarse_n;
+        if let Some((v, _)) = best {
+            parent[v] = coarse_n;
+        }
+        coarse_n += 1;
+    }
+    if coarse_n as f32 > n as f32 * 0.9 {
+        return None;
+    }
+
+    let mut masses = vec![0.0f32; coarse_n];
+    for i in 0..n {
+        masses[parent[i]] += level.masses[i];
+    }
+    let mut merged: HashMap<(usize, usize), f32> = HashMap::with_capacity(level.edges.len());
+    for &(i, j, w) in &level.edges {
+        let (pi, pj) = (parent[i], parent[j]);
+        if pi == pj {
+            continue;
+        }
+        *merged.entry((pi.min(pj), pi.max(pj))).or_insert(0.0) += w;
+    }
+    let edges = merged.into_iter().map(|((i, j), w)| (i, j, w)).collect();
+    Some((parent, Level { masses, edges }))
+}
+
+// Spring-electrical refinement with Barnes-Hut repulsion and the adaptive
+// step length of Hu (2005); nodes flagged in `fixed` do not move
+fn refine(
+    positions: &mut [(f32, f32)],
+    level: &Level,
+    fixed: &[bool],
+    config: &MultilevelConfig,
+    initial_step: f32,
+) {
+    let n = positions.len();
+    if n < 2 {
+        return;
+    }
+    let k = config.natural_length.max(1.0);
+    let c_k2 = config.repulsion * k * k;
+    let mut step = initial_step;
+    let mut energy_prev = f32::INFINITY;
+    let mut progress = 0;
+    let cooling = 0.9f32;
+
+    let mut xs = vec![0.0f32; n];
+    let mut ys = vec![0.0f32; n];
+    let mut fx = vec![0.0f32; n];
+    let mut fy = vec![0.0f32; n];
+    let mut tree = QuadTree::new();
+    for _ in 0..config.iterations {
+        for (i, &(x, y)) in positions.iter().enumerate() {
+            xs[i] = x;
+            ys[i] = y;
+        }
+        fx.iter_mut().for_each(|f| *f = 0.0);
+        fy.iter_mut().for_each(|f| *f = 0.0);
+
+        // Repulsion ~ C K^2 / d between every pair of bodies
+        tree.rebuild_with_masses(&xs, &ys, &level.masses);
+        for i in 0..n {
+            let mi = level.masses[i];
+            tree.visit(i, xs[i], ys[i], config.theta, |dx, dy, m| {
+                let f = c_k2 * mi * m / (dx * dx + dy * dy + 0.01);
+                fx[i] -= f * dx;
+                fy[i] -= f * dy;
+            });
+        }
+
+        // Gravity ~ d toward the centroid, weighted by mass
+        let (gx, gy) = centroid(positions);
+        for i in 0..n {
+            let m = config.gravity * level.masses[i];
+            fx[i] += m * (gx - xs[i]);
+            fy[i] += m * (gy - ys[i]);
+        }
+
+        // Attraction ~ d^2 / K along edges
+        for &(i, j, w) in &level.edges {
+            let dx = xs[j] - xs[i];
+            let dy = ys[j] - ys[i];
+            let f = w * (dx * dx + dy * dy).sqrt() / k;
+            fx[i] += f * dx;
+            fy[i] += f * dy;
+            fx[j] -= f * dx;
+            fy[j] -= f * dy;
+        }
+
+        // Move every free node by `step` along its net force
+        let mut energy = 0.0f32;
+        for i in 0..n {
+            if fixed.get(i).copied().unwrap_or(false) {
+                continue;
+            }
+            let f2 = fx[i] * fx[i] + fy[i] * fy[i];
+            energy += f2;
+            let f = f2.sqrt();
+            if f > 0.0 {
+                positions[i].0 += step * fx[i] / f;
+                positions[i].1 += step * fy[i] / f;
+            }
+        }
+
+        if energy < energy_prev {
+            progress += 1;
+            if progress >= 5 {
+                progress = 0;
+                step /= cooling;
+            }
+        } else {
+            progress = 0;
+            step *= cooling;
+        }
+        energy_prev = energy;
+        if step < config.tolerance * k {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    #[test]
+    fn graph_above_the_coarsest_size_gets_distinct_positions() {
+        // 15 x 15 grid, every node starting on the same spot
+        let side = 15;
+        let n = side * side;
+        let mut edges = Vec::new();
+        for i in 0..n {
+            if i % side + 1 < side {
+                edges.push(GpugEdge::new(i, i + 1));
+            }
+            if i + side < n {
+                edges.push(GpugEdge::new(i, i + side));
+            }
+        }
+        let positions = vec![(200.0, 200.0); n];
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &[],
+        };
+        let config = MultilevelConfig::default();
+        assert!(n > config.coarsest_size);
+        let result = multilevel_layout(&input, &config);
+
+        assert_eq!(result.len(), n);
+        assert!(result.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let d = ((result[i].0 - result[j].0).powi(2) + (result[i].1 - result[j].1).powi(2))
+                    .sqrt();
+                assert!(d > 1.0, "{i} and {j} {d} apart");
+            }
+        }
+    }
+}
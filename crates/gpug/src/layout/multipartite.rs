@@ -0,0 +1,199 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+use crate::edge::GpugEdge;
+use crate::layout::layered::{place_layers, CrossingHeuristic, LayerDirection, LayeredConfig};
+use crate::layout::{adjacency, attribute_ranks, centroid, recenter, Layout, LayoutInput};
+
+// Which part each node belongs to
+#[derive(Clone, Debug, PartialEq)]
+pub enum Partition {
+    // Split the graph into as few parts without internal edges as a greedy
+    // colouring finds; bipartite graphs always get two
+    Detect,
+    // One value per node, e.g. from `Graph::node_attributes`; parts are
+    // ordered by value, nodes without a value last
+    Attribute(Vec<Option<String>>),
+}
+
+// Parameters of the multipartite layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultipartiteConfig {
+    pub partition: Partition,
+    // LeftToRight puts each part in a column, TopToBottom in a row
+    pub direction: LayerDirection,
+    pub heuristic: CrossingHeuristic,
+    // Distance between consecutive parts
+    pub part_spacing: f32,
+    // Distance between neighbours within a part
+    pub node_spacing: f32,
+    // Alternating passes of crossing minimisation
+    pub sweeps: usize,
+    // Passes pulling nodes level with their neighbours; zero keeps every part
+    // evenly spaced
+    pub coordinate_passes: usize,
+}
+
+impl Default for MultipartiteConfig {
+    fn default() -> Self {
+        Self {
+            partition: Partition::Detect,
+            direction: LayerDirection::LeftToRight,
+            heuristic: CrossingHeuristic::Barycenter,
+            part_spacing: 240.0,
+            node_spacing: 30.0,
+            sweeps: 24,
+            coordinate_passes: 0,
+        }
+    }
+}
+
+// Each part of a bipartite or multipartite graph in its own column (or row),
+// ordered within the part to reduce edge crossings
+#[derive(Default)]
+pub struct MultipartiteLayout {
+    pub config: MultipartiteConfig,
+}
+
+impl MultipartiteLayout {
+    pub fn new(config: MultipartiteConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for MultipartiteLayout {
+    fn name(&self) -> &str {
+        "multipartite"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        multipartite_layout(input, &self.config)
+    }
+}
+
+// Parts are stacked in order and ordered like the layers of the layered
+// layout, with edges between non-adjacent parts routed through the parts in
+// between. Edges inside a part do not affect the ordering.
+pub fn multipartite_layout(input: &LayoutInput, config: &MultipartiteConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let part = match &config.partition {
+        Partition::Attribute(values) if values.len() == n => attribute_ranks(values),
+        _ => detect_partition(n, input.edges),
+    };
+
+    let mut edges: Vec<(usize, usize)> = input
+        .edges
+        .iter()
+        .filter(|e| e.source < n && e.target < n)
+        .filter_map(|e| {
+            let (u, v) = (e.source, e.target);
+            match part[u].cmp(&part[v]) {
+                std::cmp::Ordering::Less => Some((u, v)),
+                std::cmp::Ordering::Greater => Some((v, u)),
+                std::cmp::Ordering::Equal => None,
+            }
+        })
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+
+    let layered = LayeredConfig {
+        direction: config.direction,
+        heuristic: config.heuristic,
+        layer_spacing: config.part_spacing,
+        node_spacing: config.node_spacing,
+        sweeps: config.sweeps,
+        coordinate_passes: config.coordinate_passes,
+    };
+    let mut positions = place_layers(n, &edges, part, &layered);
+    recenter(&mut positions, centroid(input.positions));
+    positions
+}
+
+// Greedy colouring by saturation (DSatur): repeatedly colour the node with the
+// most distinct colours among its neighbours, ties to the higher degree, with
+// the smallest colour none of them has. Exact on bipartite graphs; isolated
+// nodes land in part 0.
+pub fn detect_partition(n: usize, edges: &[GpugEdge]) -> Vec<usize> {
+    let neighbors = adjacency(n, edges);
+    let mut color: Vec<Option<usize>> = vec![None; n];
+    let mut neighbor_colors: Vec<HashSet<usize>> = vec![HashSet::new(); n];
+    let mut queue: BinaryHeap<(usize, usize, Reverse<usize>)> = (0..n)
+        .map(|v| (0, neighbors[v].len(), Reverse(v)))
+        .collect();
+    while let Some((saturation, _, Reverse(u))) = queue.pop() {
+        // Skip entries superseded by a later push with higher saturation
+        if color[u].is_some() || saturation != neighbor_colors[u].len() {
+            continue;
+        }
+        let c = (0..).find(|c| !neighbor_colors[u].contains(c)).unwrap_or(0);
+        color[u] = Some(c);
+        for &v in &neighbors[u] {
+            if color[v].is_none() && neighbor_colors[v].insert(c) {
+                queue.push((neighbor_colors[v].len(), neighbors[v].len(), Reverse(v)));
+            }
+        }
+    }
+    color.into_iter().map(|c| c.unwrap_or(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bipartite_graphs_get_two_parts() {
+        // Crown graph: 2i joined to every 2j + 1 except 2i + 1. Colouring in
+        // index order would take four colours.
+        let mut edges = Vec::new();
+        for i in 0..4 {
+            for j in (0..4).filter(|&j| j != i) {
+                edges.push(GpugEdge::new(2 * i, 2 * j + 1));
+            }
+        }
+        let part = detect_partition(8, &edges);
+
+        assert_eq!(part.iter().copied().max(), Some(1));
+        assert!(edges.iter().all(|e| part[e.source] != part[e.target]));
+    }
+
+    #[test]
+    fn attribute_partition_is_respected() {
+        let values = ["b", "a", "c", "a", "b", "c"];
+        // Edges inside part "a" and across all parts
+        let edges: Vec<GpugEdge> = [(1, 3), (1, 0), (3, 4), (0, 2), (4, 5)]
+            .iter()
+            .map(|&(u, v)| GpugEdge::new(u, v))
+            .collect();
+        let positions = vec![(0.0, 0.0); values.len()];
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &[],
+        };
+        let config = MultipartiteConfig {
+            partition: Partition::Attribute(values.iter().map(|v| Some(v.to_string())).collect()),
+            ..MultipartiteConfig::default()
+        };
+        let result = multipartite_layout(&input, &config);
+
+        // One column per value, in value order
+        let column = |value: &str| {
+            let xs: Vec<f32> = (0..values.len())
+                .filter(|&v| values[v] == value)
+                .map(|v| result[v].0)
+                .collect();
+            assert!(
+                xs.iter().all(|&x| (x - xs[0]).abs() < 1e-3),
+                "{value}: {xs:?}"
+            );
+            xs[0]
+        };
+        let (a, b, c) = (column("a"), column("b"), column("c"));
+        assert!(a < b && b < c, "columns at {a}, {b}, {c}");
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+
+use crate::edge::GpugEdge;
+use crate::layout::stress::{stress_layout, StressConfig};
+use crate::layout::{adjacency, centroid, Layout, LayoutInput};
+use crate::simulation::spatial::SpatialGrid;
+
+// Above this many nodes the stress sketch uses pivots instead of all pairs
+const SPARSE_SKETCH_NODES: usize = 1000;
+const SKETCH_PIVOTS: usize = 64;
+
+// Parameters of the orthogonal grid layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrthogonalConfig {
+    // Distance between neighbouring grid lines
+    pub grid_spacing: f32,
+    // Improvement sweeps after nodes are snapped to the grid
+    pub iterations: usize,
+    // Extra cost, in grid cells, of an edge that needs a bend over one that
+    // runs along a single grid line
+    pub bend_penalty: f32,
+    // Sketch the layout with stress majorization first; otherwise the input
+    // positions are snapped as they are
+    pub use_stress: bool,
+}
+
+impl Default for OrthogonalConfig {
+    fn default() -> Self {
+        Self {
+            grid_spacing: 80.0,
+            iterations: 16,
+            bend_penalty: 0.5,
+            use_stress: true,
+        }
+    }
+}
+
+// Places every node on its own grid point, keeping edges short and, where it
+// can, on a shared grid line so they need no bend. Pair with
+// `EdgeRouting::Orthogonal` to draw edges as horizontal and vertical runs.
+#[derive(Default)]
+pub struct OrthogonalLayout {
+    pub config: OrthogonalConfig,
+}
+
+impl OrthogonalLayout {
+    pub fn new(config: OrthogonalConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for OrthogonalLayout {
+    fn name(&self) -> &str {
+        "orthogonal"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        orthogonal_layout(input, &self.config)
+    }
+
+    fn grid_spacing(&self) -> Option<f32> {
+        Some(self.config.grid_spacing)
+    }
+}
+
+type Cell = (i32, i32);
+
+// Snaps a sketch of the graph to the grid points at multiples of
+// `grid_spacing`, giving high-degree nodes first pick of the point nearest
+// their sketch position, then moves or swaps nodes between points while that
+// shortens the Manhattan length of their edges. Pinned nodes keep their input
+// position and their grid point is left to them.
+pub fn orthogonal_layout(input: &LayoutInput, config: &OrthogonalConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let spacing = config.grid_spacing.max(1.0);
+    let neighbors = adjacency(n, input.edges);
+    let any_pinned = (0..n).any(|i| input.is_pinned(i));
+
+    let mut sketch = if config.use_stress && n > 1 {
+        let stress = StressConfig {
+            edge_length: spacing,
+            pivots: (n > SPARSE_SKETCH_NODES).then_some(SKETCH_PIVOTS),
+            use_input_positions: true,
+            ..StressConfig::default()
+        };
+        stress_layout(input, &stress)
+    } else {
+        input.positions.to_vec()
+    };
+    // Turn edges toward the axes and scale so an average edge spans one
+    // cell; pinned nodes fix the frame
+    if !any_pinned {
+        let (sin, cos) = axis_rotation(&sketch, &neighbors).sin_cos();
+        let mean = mean_edge_length(&sketch, &neighbors);
+        let scale = if mean > 1e-3 { spacing / mean } else { 1.0 };
+        let (sx, sy) = centroid(&sketch);
+        let (cx, cy) = centroid(input.positions);
+        for p in sketch.iter_mut() {
+            let (dx, dy) = (p.0 - sx, p.1 - sy);
+            *p = (
+                cx + (dx * cos - dy * sin) * scale,
+                cy + (dx * sin + dy * cos) * scale,
+            );
+        }
+    }
+
+    let ideal: Vec<(f32, f32)> = sketch
+        .iter()
+        .map(|&(x, y)| (x / spacing, y / spacing))
+        .collect();
+    let mut cells: Vec<Cell> = vec![(0, 0); n];
+    let mut occupant: HashMap<Cell, usize> = HashMap::with_capacity(n * 2);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| (!input.is_pinned(i), std::cmp::Reverse(neighbors[i].len())));
+    for i in order {
+        let cell = if input.is_pinned(i) {
+            let (x, y) = input.positions[i];
+            ((x / spacing).round() as i32, (y / spacing).round() as i32)
+        } else {
+            nearest_free(ideal[i], &occupant)
+        };
+        cells[i] = cell;
+        occupant.entry(cell).or_insert(i);
+    }
+
+    let cost = |cells: &[Cell], i: usize| -> f32 {
+        let (x, y) = cells[i];
+        neighbors[i]
+            .iter()
+            .map(|&j| {
+                let dx = (cells[j].0 - x).abs();
+                let dy = (cells[j].1 - y).abs();
+                let bend = if dx != 0 && dy != 0 {
+                    config.bend_penalty
+                } else {
+                    0.0
+                };
+                (dx + dy) as f32 + bend
+            })
+            .sum()
+    };
+    for _ in 0..config.iterations {
+        let mut improved = false;
+        for u in 0..n {
+            if input.is_pinned(u) || neighbors[u].is_empty() {
+                continue;
+            }
+            let from = cells[u];
+            let mut candidates: Vec<Cell> = Vec::with_capacity(9);
+            candidates.push(median_cell(&neighbors[u], &cells));
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if dx != 0 || dy != 0 {
+                        candidates.push((from.0 + dx, from.1 + dy));
+                    }
+                }
+            }
+            for to in candidates {
+                if to == from {
+                    continue;
+                }
+                match occupant.get(&to).copied() {
+                    None => {
+                        let before = cost(&cells, u);
+                        cells[u] = to;
+                        if cost(&cells, u) < before - 1e-4 {
+                            occupant.remove(&from);
+                            occupant.insert(to, u);
+                            improved = true;
+                            break;
+                        }
+                        cells[u] = from;
+                    }
+                    Some(v) if !input.is_pinned(v) => {
+                        let before = cost(&cells, u) + cost(&cells, v);
+                        cells.swap(u, v);
+                        if cost(&cells, u) + cost(&cells, v) < before - 1e-4 {
+                            occupant.insert(to, u);
+                            occupant.insert(from, v);
+                            improved = true;
+                            break;
+                        }
+                        cells.swap(u, v);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    (0..n)
+        .map(|i| {
+            if input.is_pinned(i) {
+                input.positions[i]
+            } else {
+                (cells[i].0 as f32 * spacing, cells[i].1 as f32 * spacing)
+            }
+        })
+        .collect()
+}
+
+fn mean_edge_length(positions: &[(f32, f32)], neighbors: &[Vec<usize>]) -> f32 {
+    let mut total = 0.0f32;
+    let mut count = 0usize;
+    for (i, adjacent) in neighbors.iter().enumerate() {
+        for &j in adjacent {
+            let (dx, dy) = (
+                positions[j].0 - positions[i].0,
+                positions[j].1 - positions[i].1,
+            );
+            total += (dx * dx + dy * dy).sqrt();
+            count += 1;
+        }
+    }
+    if count > 0 {
+        total / count as f32
+    } else {
+        0.0
+    }
+}
+
+// Rotation that brings edge directions closest to the axes: directions are
+// averaged with their angles multiplied by four, which maps all four axis
+// directions to the same point
+fn axis_rotation(positions: &[(f32, f32)], neighbors: &[Vec<usize>]) -> f32 {
+    let (mut sum_cos, mut sum_sin) = (0.0f32, 0.0f32);
+    for (i, adjacent) in neighbors.iter().enumerate() {
+        for &j in adjacent {
+            let (dx, dy) = (
+                positions[j].0 - positions[i].0,
+                positions[j].1 - positions[i].1,
+            );
+            let weight = (dx * dx + dy * dy).sqrt();
+            let angle = 4.0 * dy.atan2(dx);
+            sum_cos += weight * angle.cos();
+            sum_sin += weight * angle.sin();
+        }
+    }
+    -sum_sin.atan2(sum_cos) / 4.0
+}
+
+// Free grid point closest to `(x, y)`, searching square rings outward until
+// they lie further out than the best free point found
+fn nearest_free((x, y): (f32, f32), occupant: &HashMap<Cell, usize>) -> Cell {
+    let (cx, cy) = (x.round() as i32, y.round() as i32);
+    let mut best: Option<(f32, Cell)> = None;
+    let mut radius = 0i32;
+    loop {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx.abs() != radius && dy.abs() != radius {
+                    continue;
+                }
+                let cell = (cx + dx, cy + dy);
+                if occupant.contains_key(&cell) {
+                    continue;
+                }
+                let d = (cell.0 as f32 - x).powi(2) + (cell.1 as f32 - y).powi(2);
+                if best.is_none_or(|(b, _)| d < b) {
+                    best = Some((d, cell));
+                }
+            }
+        }
+        if let Some((d, cell)) = best {
+            if (radius as f32) * (radius as f32) >= d {
+                return cell;
+            }
+        }
+        radius += 1;
+    }
+}
+
+// Grid point minimising the summed Manhattan distance to `adjacent`
+fn median_cell(adjacent: &[usize], cells: &[Cell]) -> Cell {
+    let mut xs: Vec<i32> = adjacent.iter().map(|&j| cells[j].0).collect();
+    let mut ys: Vec<i32> = adjacent.iter().map(|&j| cells[j].1).collect();
+    let mid = xs.len() / 2;
+    let (_, &mut x, _) = xs.select_nth_unstable(mid);
+    let (_, &mut y, _) = ys.select_nth_unstable(mid);
+    (x, y)
+}
+
+// Horizontal and vertical polyline for every edge between node centres, in
+// edge order; out-of-range edges get an empty route. Each edge takes the
+// straight, L, Z or U shaped route with the fewest bends among those whose
+// segments stay clear of other nodes' discs, or the one touching the fewest.
+pub fn orthogonal_routes(
+    centres: &[(f32, f32)],
+    radii: &[f32],
+    edges: &[GpugEdge],
+) -> Vec<Vec<(f32, f32)>> {
+    let n = centres.len();
+    let max_radius = radii.iter().copied().fold(1.0f32, f32::max);
+    let mut grid = SpatialGrid::new();
+    grid.rebuild(n, max_radius * 2.0, |i| centres[i]);
+    let radius = |k: usize| radii.get(k).copied().unwrap_or(0.0);
+
+    // Nodes other than the endpoints whose disc a route's segments touch
+    let hits = |route: &[(f32, f32)], ends: (usize, usize)| -> usize {
+        let mut count = 0;
+        for segment in route.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            let (lo_x, hi_x) = (x0.min(x1) - max_radius, x0.max(x1) + max_radius);
+            let (lo_y, hi_y) = (y0.min(y1) - max_radius, y0.max(y1) + max_radius);
+            let (c0, c1) = (grid.cell_of(lo_x, lo_y), grid.cell_of(hi_x, hi_y));
+            for gy in c0.1..=c1.1 {
+                for gx in c0.0..=c1.0 {
+                    grid.for_each_in_cell((gx, gy), |k, x, y| {
+                        if k == ends.0 || k == ends.1 {
+                            return;
+                        }
+                        // Distance from the centre to the axis-aligned segment
+                        let dx = x - x.clamp(x0.min(x1), x0.max(x1));
+                        let dy = y - y.clamp(y0.min(y1), y0.max(y1));
+                        if dx * dx + dy * dy < radius(k) * radius(k) {
+                            count += 1;
+                        }
+                    });
+                }
+            }
+        }
+        count
+    };
+
+    edges
+        .iter()
+        .map(|edge| {
+            let (i, j) = (edge.source, edge.target);
+            if i >= n || j >= n {
+                return Vec::new();
+            }
+            let (a, b) = (centres[i], centres[j]);
+            let detour = (radius(i).max(radius(j)) * 2.0).max(max_radius * 2.0);
+            let mut best: Option<(usize, Vec<(f32, f32)>)> = None;
+            for route in candidate_routes(a, b, detour) {
+                let count = hits(&route, (i, j));
+                if best.as_ref().is_none_or(|(b, _)| count < *b) {
+                    best = Some((count, route));
+                }
+                if count == 0 {
+                    break;
+                }
+            }
+            best.map(|(_, route)| route).unwrap_or_default()
+        })
+        .collect()
+}
+
+// Orthogonal routes from `a` to `b` in order of increasing bends
+fn candidate_routes(a: (f32, f32), b: (f32, f32), detour: f32) -> Vec<Vec<(f32, f32)>> {
+    let ((ax, ay), (bx, by)) = (a, b);
+    let (mx, my) = ((ax + bx) * 0.5, (ay + by) * 0.5);
+    if (ay - by).abs() < 0.5 {
+        vec![
+            vec![a, b],
+            vec![a, (ax, ay - detour), (bx, by - detour), b],
+            vec![a, (ax, ay + detour), (bx, by + detour), b],
+        ]
+    } else if (ax - bx).abs() < 0.5 {
+        vec![
+            vec![a, b],
+            vec![a, (ax - detour, ay), (bx - detour, by), b],
+            vec![a, (ax + detour, ay), (bx + detour, by), b],
+        ]
+    } else {
+        vec![
+            vec![a, (bx, ay), b],
+            vec![a, (ax, by), b],
+            vec![a, (mx, ay), (mx, by), b],
+            vec![a, (ax, my), (bx, my), b],
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn nodes_get_distinct_grid_points() {
+        let side = 6;
+        let mut edges = Vec::new();
+        for i in 0..side * side {
+            if i % side + 1 < side {
+                edges.push(GpugEdge::new(i, i + 1));
+            }
+            if i + side < side * side {
+                edges.push(GpugEdge::new(i, i + side));
+            }
+        }
+        // Everything starts on one spot, so snapping alone cannot separate nodes
+        let positions = vec![(10.0, 10.0); side * side];
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &[],
+        };
+        let config = OrthogonalConfig::default();
+        let result = orthogonal_layout(&input, &config);
+        let cells: HashSet<Cell> = result
+            .iter()
+            .map(|&(x, y)| {
+                let (gx, gy) = (x / config.grid_spacing, y / config.grid_spacing);
+                assert!((gx - gx.round()).abs() < 1e-3 && (gy - gy.round()).abs() < 1e-3);
+                (gx.round() as i32, gy.round() as i32)
+            })
+            .collect();
+        assert_eq!(cells.len(), result.len());
+    }
+}
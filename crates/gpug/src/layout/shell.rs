@@ -0,0 +1,135 @@
+use std::f32::consts::TAU;
+
+use crate::layout::{adjacency, attribute_ranks, centroid, Layout, LayoutInput};
+
+// What puts nodes on the same shell
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShellGrouping {
+    // Highest degree innermost
+    Degree,
+    // One value per node, e.g. from `Graph::node_attributes`; smallest value
+    // innermost, nodes without a value outermost
+    Attribute(Vec<Option<String>>),
+}
+
+// Parameters of the shell layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShellConfig {
+    pub grouping: ShellGrouping,
+    // Minimum gap between consecutive shells
+    pub shell_spacing: f32,
+    // Arc length between neighbouring nodes on a shell; crowded shells grow
+    pub node_spacing: f32,
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self {
+            grouping: ShellGrouping::Degree,
+            shell_spacing: 80.0,
+            node_spacing: 30.0,
+        }
+    }
+}
+
+// Concentric circles around the input centroid, one per group
+#[derive(Default)]
+pub struct ShellLayout {
+    pub config: ShellConfig,
+}
+
+impl ShellLayout {
+    pub fn new(config: ShellConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for ShellLayout {
+    fn name(&self) -> &str {
+        "shell"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        shell_layout(input, &self.config)
+    }
+}
+
+pub fn shell_layout(input: &LayoutInput, config: &ShellConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let ranks: Vec<usize> = match &config.grouping {
+        ShellGrouping::Attribute(values) if values.len() == n => attribute_ranks(values),
+        _ => {
+            let degrees: Vec<usize> = adjacency(n, input.edges).iter().map(Vec::len).collect();
+            let mut distinct = degrees.clone();
+            distinct.sort_unstable_by(|a, b| b.cmp(a));
+            distinct.dedup();
+            degrees
+                .iter()
+                .map(|d| distinct.binary_search_by(|x| d.cmp(x)).unwrap_or(0))
+                .collect()
+        }
+    };
+    let shell_count = ranks.iter().copied().max().unwrap_or(0) + 1;
+    let mut shells: Vec<Vec<usize>> = vec![Vec::new(); shell_count];
+    for (v, &rank) in ranks.iter().enumerate() {
+        shells[rank].push(v);
+    }
+
+    let (cx, cy) = centroid(input.positions);
+    let mut positions = vec![(0.0, 0.0); n];
+    let mut radius: Option<f32> = None;
+    for shell in shells.iter().filter(|shell| !shell.is_empty()) {
+        // A lone innermost node sits in the centre
+        let needed = shell.len() as f32 * config.node_spacing / TAU;
+        let r = match radius {
+            None if shell.len() == 1 => 0.0,
+            None => needed.max(config.shell_spacing),
+            Some(previous) => needed.max(previous + config.shell_spacing),
+        };
+        radius = Some(r);
+        for (slot, &v) in shell.iter().enumerate() {
+            let angle = TAU * slot as f32 / shell.len() as f32;
+            positions[v] = (cx + r * angle.cos(), cy + r * angle.sin());
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_sit_at_their_shell_radius() {
+        let groups = ["a", "b", "b", "b", "b", "c", "c", "c", "c", "c", "c"];
+        let positions = vec![(100.0, 100.0); groups.len()];
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &[],
+            pinned: &[],
+            radii: &[],
+        };
+        let config = ShellConfig {
+            grouping: ShellGrouping::Attribute(
+                groups.iter().map(|g| Some(g.to_string())).collect(),
+            ),
+            ..ShellConfig::default()
+        };
+        let result = shell_layout(&input, &config);
+
+        // A lone inner node in the centre, then one shell spacing per shell
+        for (v, group) in groups.iter().enumerate() {
+            let expected = match *group {
+                "a" => 0.0,
+                "b" => config.shell_spacing,
+                _ => 2.0 * config.shell_spacing,
+            };
+            let (x, y) = result[v];
+            let r = ((x - 100.0).powi(2) + (y - 100.0).powi(2)).sqrt();
+            assert!((r - expected).abs() < 1e-3, "node {v} at radius {r}");
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::generators::utils::rand_f32;
+use crate::layout::{adjacency, centroid, Layout, LayoutInput};
+
+// Eigenvectors solved for at once; two more than needed so nearly equal
+// eigenvalues (square meshes, rings) converge as quickly as distinct ones
+const BLOCK_SIZE: usize = 4;
+
+// Parameters of the spectral layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralConfig {
+    // Mean edge length of the result, matching the simulation's link distance
+    pub edge_length: f32,
+    // Cap on solver iterations
+    pub max_iterations: usize,
+    // Stop once both eigenvector residuals are below this
+    pub tolerance: f64,
+    pub seed: u64,
+}
+
+impl Default for SpectralConfig {
+    fn default() -> Self {
+        Self {
+            edge_length: 40.0,
+            max_iterations: 500,
+            tolerance: 1e-5,
+            seed: 0x5BEC_7A1D_EC0D_E5EE,
+        }
+    }
+}
+
+// Places nodes by the 2nd and 3rd eigenvectors of the normalised Laplacian.
+// Cheap and deterministic, so a good starting point for the force simulation
+// in place of the random scatter from `generate_nodes`.
+#[derive(Default)]
+pub struct SpectralLayout {
+    pub config: SpectralConfig,
+}
+
+impl SpectralLayout {
+    pub fn new(config: SpectralConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for SpectralLayout {
+    fn name(&self) -> &str {
+        "spectral"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        spectral_layout(input, &self.config)
+    }
+}
+
+// The smallest non-trivial eigenvectors of I - D^-1/2 A D^-1/2 are the largest
+// of S = D^-1/2 A D^-1/2 orthogonal to its top eigenvector sqrt(degree). They
+// are found with LOBPCG, where each iteration costs a few sparse products,
+// O(edges), and are scaled back by D^-1/2 (Koren's degree-normalised
+// eigenvectors). Disconnected components collapse towards separate points, so
+// run the force simulation afterwards to spread them.
+pub fn spectral_layout(input: &LayoutInput, config: &SpectralConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n < 3 {
+        return input.positions.to_vec();
+    }
+    let neighbors = adjacency(n, input.edges);
+    let inv_sqrt_degree: Vec<f64> = neighbors
+        .iter()
+        .map(|a| 1.0 / (a.len().max(1) as f64).sqrt())
+        .collect();
+    let operator = |v: &[f64]| -> Vec<f64> {
+        neighbors
+            .iter()
+            .enumerate()
+            .map(|(i, adjacent)| {
+                let sum: f64 = adjacent.iter().map(|&j| v[j] * inv_sqrt_degree[j]).sum();
+                sum * inv_sqrt_degree[i]
+            })
+            .collect()
+    };
+    let mut trivial: Vec<f64> = inv_sqrt_degree.iter().map(|d| 1.0 / d).collect();
+    normalize(&mut trivial);
+
+    let block = BLOCK_SIZE.min(n - 1);
+    let wanted = 2.min(block);
+    let mut seed = config.seed;
+    let start: Vec<Vec<f64>> = (0..block)
+        .map(|_| (0..n).map(|_| rand_f32(&mut seed) as f64 - 0.5).collect())
+        .collect();
+    let mut x = extend_basis(Vec::new(), start, &trivial);
+    let mut sx: Vec<Vec<f64>> = x.iter().map(|v| operator(v)).collect();
+    let mut theta: Vec<f64> = x.iter().zip(&sx).map(|(v, s)| dot(v, s)).collect();
+    let mut p: Vec<Vec<f64>> = Vec::new();
+    for _ in 0..config.max_iterations {
+        let residuals: Vec<Vec<f64>> = (0..x.len())
+            .map(|k| {
+                sx[k]
+                    .iter()
+                    .zip(&x[k])
+                    .map(|(s, v)| s - theta[k] * v)
+                    .collect()
+            })
+            .collect();
+        let converged = residuals
+            .iter()
+            .take(wanted)
+            .all(|r| dot(r, r).sqrt() <= config.tolerance);
+        if converged {
+            break;
+        }
+
+        // Rayleigh-Ritz over span[X, R, P], kept orthogonal to the trivial vector
+        let x_count = x.len();
+        let mut search = residuals;
+        search.append(&mut p);
+        let basis = extend_basis(x, search, &trivial);
+        let images: Vec<Vec<f64>> = basis.iter().map(|b| operator(b)).collect();
+        let m = basis.len();
+        let mut gram = vec![vec![0.0f64; m]; m];
+        for i in 0..m {
+            for j in i..m {
+                let value = dot(&basis[i], &images[j]);
+                gram[i][j] = value;
+                gram[j][i] = value;
+            }
+        }
+        let (values, vectors) = symmetric_eigen(gram);
+        let mut order: Vec<usize> = (0..m).collect();
+        order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+        order.truncate(block.min(m));
+
+        let combine = |vectors_of: &[Vec<f64>], from: usize, column: usize| -> Vec<f64> {
+            let mut out = vec![0.0f64; n];
+            for (row, b) in vectors_of.iter().enumerate().skip(from) {
+                let c = vectors[row][column];
+                out.iter_mut().zip(b).for_each(|(o, v)| *o += c * v);
+            }
+            out
+        };
+        theta = order.iter().map(|&c| values[c]).collect();
+        x = order.iter().map(|&c| combine(&basis, 0, c)).collect();
+        sx = order.iter().map(|&c| combine(&images, 0, c)).collect();
+        // Search directions: the new iterate minus its part in the old X
+        p = if m > x_count {
+            order.iter().map(|&c| combine(&basis, x_count, c)).collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    let coordinate = |k: usize| -> Vec<f64> {
+        match x.get(k) {
+            Some(v) => v.iter().zip(&inv_sqrt_degree).map(|(v, d)| v * d).collect(),
+            None => vec![0.0; n],
+        }
+    };
+    let (xs, ys) = (coordinate(0), coordinate(1));
+    let mut total = 0.0f64;
+    let mut count = 0usize;
+    for (i, adjacent) in neighbors.iter().enumerate() {
+        for &j in adjacent {
+            total += ((xs[i] - xs[j]).powi(2) + (ys[i] - ys[j]).powi(2)).sqrt();
+            count += 1;
+        }
+    }
+    let mean = if count > 0 { total / count as f64 } else { 0.0 };
+    let scale = if mean > 1e-12 {
+        config.edge_length as f64 / mean
+    } else {
+        config.edge_length as f64
+    };
+    let (cx, cy) = centroid(input.positions);
+    (0..n)
+        .map(|i| (cx + (xs[i] * scale) as f32, cy + (ys[i] * scale) as f32))
+        .collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(u: &mut [f64]) -> f64 {
+    let norm = dot(u, u).sqrt();
+    if norm > 0.0 {
+        u.iter_mut().for_each(|v| *v /= norm);
+    }
+    norm
+}
+
+// Append `vectors` to an orthonormal `basis` by Gram-Schmidt (applied twice
+// for stability) against `trivial` and the basis, dropping vectors that are
+// numerically dependent
+fn extend_basis(
+    mut basis: Vec<Vec<f64>>,
+    vectors: Vec<Vec<f64>>,
+    trivial: &[f64],
+) -> Vec<Vec<f64>> {
+    for mut v in vectors {
+        let before = dot(&v, &v).sqrt();
+        for _ in 0..2 {
+            for q in std::iter::once(trivial).chain(basis.iter().map(Vec::as_slice)) {
+                let c = dot(&v, q);
+                v.iter_mut().zip(q).for_each(|(a, b)| *a -= c * b);
+            }
+        }
+        if before > 0.0 && normalize(&mut v) > 1e-8 * before {
+            basis.push(v);
+        }
+    }
+    basis
+}
+
+// Eigenvalues and eigenvectors (as columns) of a small symmetric matrix by
+// cyclic Jacobi rotations
+fn symmetric_eigen(mut a: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let m = a.len();
+    let mut v = vec![vec![0.0f64; m]; m];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for _ in 0..100 {
+        let off: f64 = (0..m)
+            .flat_map(|i| (0..m).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off < 1e-24 {
+            break;
+        }
+        for p in 0..m {
+            for q in p + 1..m {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let tau = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = tau.signum() / (tau.abs() + (1.0 + tau * tau).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = t * c;
+                // A <- J^T A J, V <- V J
+                for row in a.iter_mut().chain(v.iter_mut()) {
+                    let (rp, rq) = (row[p], row[q]);
+                    row[p] = c * rp - s * rq;
+                    row[q] = s * rp + c * rq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (ap, aq) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (rp, rq) = (*ap, *aq);
+                    *ap = c * rp - s * rq;
+                    *aq = s * rp + c * rq;
+                }
+            }
+        }
+    }
+    ((0..m).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    #[test]
+    fn ring_lies_on_a_circle() {
+        let n = 24;
+        let positions = vec![(0.0, 0.0); n];
+        let edges: Vec<GpugEdge> = (0..n).map(|i| GpugEdge::new(i, (i + 1) % n)).collect();
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &[],
+        };
+        let result = spectral_layout(&input, &SpectralConfig::default());
+        let (cx, cy) = centroid(&result);
+        let radii: Vec<f32> = result
+            .iter()
+            .map(|&(x, y)| ((x - cx).powi(2) + (y - cy).powi(2)).sqrt())
+            .collect();
+        let min = radii.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = radii.iter().copied().fold(0.0, f32::max);
+        assert!(min > 0.0 && max / min < 1.01, "radii from {min} to {max}");
+    }
+}
@@ -0,0 +1,297 @@
+use crate::generators::utils::rand_f32;
+use crate::layout::{
+    centroid, shortest_paths, shortest_paths_from, weighted_adjacency, Layout, LayoutInput,
+};
+use crate::simulation::constraints::{project_constraints, Constraint};
+
+// Parameters of the stress majorization layout
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressConfig {
+    // Pixel length of an edge without its own `GpugEdge::length`
+    pub edge_length: f32,
+    pub iterations: usize,
+    // Stop once an iteration lowers the stress by less than this fraction
+    pub tolerance: f32,
+    // Approximate the all-pairs terms with this many pivots (sparse stress).
+    // None uses the full n * n distance matrix.
+    pub pivots: Option<usize>,
+    // Start from the input positions instead of a seeded random placement
+    pub use_input_positions: bool,
+    pub seed: u64,
+    // Projected onto after every iteration, acting on disc centres like the
+    // force simulation's
+    pub constraints: Vec<Constraint>,
+    pub constraint_iterations: usize,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            edge_length: 50.0,
+            iterations: 300,
+            tolerance: 1e-4,
+            pivots: None,
+            use_input_positions: false,
+            seed: 0x57E5_5ED0_0D15_7A1C,
+            constraints: Vec::new(),
+            constraint_iterations: 4,
+        }
+    }
+}
+
+// Stress majorization (SMACOF): places nodes so their distance matches the
+// graph distance, weighted by 1 / d^2, by repeatedly moving each node to the
+// weighted average of where every other node wants it
+#[derive(Default)]
+pub struct StressLayout {
+    pub config: StressConfig,
+}
+
+impl StressLayout {
+    pub fn new(config: StressConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Layout for StressLayout {
+    fn name(&self) -> &str {
+        "stress"
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        stress_layout(input, &self.config)
+    }
+
+    fn is_anchored(&self) -> bool {
+        self.config.constraints.iter().any(Constraint::is_absolute)
+    }
+}
+
+// One stress term of a node: target node, ideal distance and weight
+type Term = (usize, f32, f32);
+
+// Pinned nodes keep their input position and only act as anchors for the
+// rest, so they constrain the layout without being moved by it
+pub fn stress_layout(input: &LayoutInput, config: &StressConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n < 2 {
+        return input.positions.to_vec();
+    }
+    let length = config.edge_length.max(1.0);
+    let terms = match config.pivots {
+        Some(pivots) if pivots < n => sparse_terms(input, length, pivots.max(1)),
+        _ => full_terms(input, length),
+    };
+
+    let mut positions: Vec<(f32, f32)> = if config.use_input_positions {
+        input.positions.to_vec()
+    } else {
+        let side = length * (n as f32).sqrt();
+        let (cx, cy) = centroid(input.positions);
+        let mut seed = config.seed;
+        (0..n)
+            .map(|i| {
+                let x = cx + (rand_f32(&mut seed) - 0.5) * side;
+                let y = cy + (rand_f32(&mut seed) - 0.5) * side;
+                if input.is_pinned(i) {
+                    input.positions[i]
+                } else {
+                    (x, y)
+                }
+            })
+            .collect()
+    };
+
+    let (mut xs, mut ys) = (Vec::with_capacity(n), Vec::with_capacity(n));
+    let mut previous = f32::INFINITY;
+    for _ in 0..config.iterations {
+        let mut stress = 0.0f32;
+        for i in 0..n {
+            let (xi, yi) = positions[i];
+            let (mut sx, mut sy, mut sw) = (0.0f32, 0.0f32, 0.0f32);
+            for &(j, d, w) in &terms[i] {
+                let (xj, yj) = positions[j];
+                let (dx, dy) = (xi - xj, yi - yj);
+                let dist = (dx * dx + dy * dy).sqrt();
+                stress += w * (dist - d) * (dist - d);
+                // Where j wants i: distance d from j along the current direction
+                let (ux, uy) = if dist > 1e-3 {
+                    (dx / dist, dy / dist)
+                } else {
+                    // Coincident nodes: push apart along an index-based direction
+                    let angle = (i * 7 + j) as f32;
+                    (angle.cos(), angle.sin())
+                };
+                sx += w * (xj + d * ux);
+                sy += w * (yj + d * uy);
+                sw += w;
+            }
+            if !input.is_pinned(i) && sw > 0.0 {
+                positions[i] = (sx / sw, sy / sw);
+            }
+        }
+        if !config.constraints.is_empty() {
+            xs.clear();
+            ys.clear();
+            for &(x, y) in &positions {
+                xs.push(x);
+                ys.push(y);
+            }
+            project_constraints(
+                &config.constraints,
+                &mut xs,
+                &mut ys,
+                input.radii,
+                input.pinned,
+                config.constraint_iterations,
+            );
+            for (p, (&x, &y)) in positions.iter_mut().zip(xs.iter().zip(&ys)) {
+                *p = (x, y);
+            }
+        }
+        if previous.is_finite() && (previous - stress) <= config.tolerance * previous {
+            break;
+        }
+        previous = stress;
+    }
+    positions
+}
+
+// Every other node with weight 1 / d^2. Disconnected pairs sit a hop beyond
+// the farthest connected pair so components do not drift apart.
+fn full_terms(input: &LayoutInput, length: f32) -> Vec<Vec<Term>> {
+    let n = input.len();
+    let distances = shortest_paths(n, input.edges, length);
+    let max_finite = distances
+        .iter()
+        .copied()
+        .filter(|d| d.is_finite())
+        .fold(0.0f32, f32::max);
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .filter(|&j| j != i)
+                .map(|j| {
+                    let d = distances[i * n + j];
+                    let d = if d.is_finite() {
+                        d
+                    } else {
+                        max_finite + length
+                    };
+                    let d = d.max(1e-3);
+                    (j, d, 1.0 / (d * d))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Sparse stress (Ortmann, Klimenta, Brandes 2016): exact terms for graph
+// neighbours plus one term per pivot, weighted by how many nodes of the
+// pivot's region it stands in for. Needs only pivots * n distances.
+fn sparse_terms(input: &LayoutInput, length: f32, pivots: usize) -> Vec<Vec<Term>> {
+    let n = input.len();
+    let neighbors = weighted_adjacency(n, input.edges, length);
+
+    // Max-min pivot selection: each new pivot is the node farthest from all
+    // previous ones, so unreached components get a pivot first
+    let mut chosen: Vec<usize> = Vec::with_capacity(pivots);
+    let mut rows: Vec<Vec<f32>> = Vec::with_capacity(pivots);
+    let mut nearest = vec![f32::INFINITY; n];
+    let mut region = vec![0usize; n];
+    let mut next = 0;
+    for p in 0..pivots {
+        let mut row = vec![0.0f32; n];
+        shortest_paths_from(&neighbors, next, &mut row);
+        for v in 0..n {
+            if row[v] < nearest[v] {
+                nearest[v] = row[v];
+                region[v] = p;
+            }
+        }
+        chosen.push(next);
+        rows.push(row);
+        next = (0..n)
+            .filter(|v| !chosen.contains(v))
+            .max_by(|&a, &b| nearest[a].total_cmp(&nearest[b]))
+            .unwrap_or(next);
+    }
+
+    // Distances from each pivot to the members of its region, sorted so the
+    // members within half of d(p, i) can be counted by binary search
+    let mut members: Vec<Vec<f32>> = vec![Vec::new(); pivots];
+    for v in 0..n {
+        if nearest[v].is_finite() {
+            members[region[v]].push(nearest[v]);
+        }
+    }
+    for list in members.iter_mut() {
+        list.sort_by(f32::total_cmp);
+    }
+
+    (0..n)
+        .map(|i| {
+            let mut terms: Vec<Term> = neighbors[i]
+                .iter()
+                .map(|&(j, d)| {
+                    let d = d.max(1e-3);
+                    (j, d, 1.0 / (d * d))
+                })
+                .collect();
+            for (p, &pivot) in chosen.iter().enumerate() {
+                let d = rows[p][i];
+                if pivot == i || !d.is_finite() || neighbors[i].iter().any(|&(j, _)| j == pivot) {
+                    continue;
+                }
+                let d = d.max(1e-3);
+                let represented = members[p].partition_point(|&m| m <= d * 0.5).max(1);
+                terms.push((pivot, d, represented as f32 / (d * d)));
+            }
+            terms
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    // A path of four nodes and a separate triangle, every node starting at
+    // the same spot except the pinned one
+    fn two_components() -> (Vec<(f32, f32)>, Vec<GpugEdge>, Vec<bool>) {
+        let mut positions = vec![(100.0, 100.0); 7];
+        positions[1] = (300.0, -40.0);
+        let edges = [(0, 1), (1, 2), (2, 3), (4, 5), (5, 6), (6, 4)]
+            .iter()
+            .map(|&(i, j)| GpugEdge::new(i, j))
+            .collect();
+        let mut pinned = vec![false; 7];
+        pinned[1] = true;
+        (positions, edges, pinned)
+    }
+
+    #[test]
+    fn pinned_nodes_keep_their_position_and_components_stay_finite() {
+        let (positions, edges, pinned) = two_components();
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &pinned,
+            radii: &[],
+        };
+        for pivots in [None, Some(3)] {
+            let config = StressConfig {
+                pivots,
+                ..StressConfig::default()
+            };
+            let result = stress_layout(&input, &config);
+            assert_eq!(result.len(), positions.len());
+            assert_eq!(result[1], positions[1], "pivots {pivots:?}");
+            assert!(
+                result.iter().all(|p| p.0.is_finite() && p.1.is_finite()),
+                "pivots {pivots:?}"
+            );
+        }
+    }
+}
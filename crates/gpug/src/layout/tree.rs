@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+use crate::layout::{adjacency, Layout, LayoutInput};
+
+// How the spanning tree is drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeStyle {
+    // Reingold-Tilford: root on top, each depth on its own row
+    Tidy,
+    // The tidy order wrapped around the root, each depth on its own ring
+    Radial,
+}
+
+// Parameters of the tree layouts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TreeConfig {
+    pub style: TreeStyle,
+    // Node the tree hangs from; None picks the highest-degree node
+    pub root: Option<usize>,
+    // Distance between consecutive depths (rows or rings)
+    pub level_spacing: f32,
+    // Minimum distance between neighbouring subtrees on a row
+    pub sibling_spacing: f32,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            style: TreeStyle::Tidy,
+            root: None,
+            level_spacing: 80.0,
+            sibling_spacing: 40.0,
+        }
+    }
+}
+
+// Lays out a breadth-first spanning tree of the graph, so non-tree edges are
+// ignored for placement but still drawn
+#[derive(Default)]
+pub struct TreeLayout {
+    pub config: TreeConfig,
+}
+
+impl TreeLayout {
+    pub fn new(config: TreeConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn from_root(root: usize, style: TreeStyle) -> Self {
+        Self::new(TreeConfig {
+            style,
+            root: Some(root),
+            ..TreeConfig::default()
+        })
+    }
+}
+
+impl Layout for TreeLayout {
+    fn name(&self) -> &str {
+        match self.config.style {
+            TreeStyle::Tidy => "tree",
+            TreeStyle::Radial => "radial tree",
+        }
+    }
+
+    fn layout(&mut self, input: &LayoutInput) -> Vec<(f32, f32)> {
+        tree_layout(input, &self.config)
+    }
+}
+
+// The root keeps its input position. Other components hang from their own
+// highest-degree node next to the root's tree (tidy) or on the first ring
+// around an empty centre (radial).
+pub fn tree_layout(input: &LayoutInput, config: &TreeConfig) -> Vec<(f32, f32)> {
+    let n = input.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let neighbors = adjacency(n, input.edges);
+    let by_degree =
+        |a: &usize, b: &usize| neighbors[*a].len().cmp(&neighbors[*b].len()).then(b.cmp(a));
+    let root = config
+        .root
+        .filter(|&r| r < n)
+        .or_else(|| (0..n).max_by(by_degree))
+        .unwrap_or(0);
+
+    // Breadth-first spanning forest. Index n is a virtual root joining the
+    // component roots so the whole forest is laid out as one tree.
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n + 1];
+    let mut depth = vec![0usize; n + 1];
+    let mut visited = vec![false; n];
+    let mut component_roots = vec![root];
+    let mut queue = VecDeque::new();
+    loop {
+        let start = *component_roots.last().unwrap_or(&root);
+        visited[start] = true;
+        children[n].push(start);
+        depth[start] = 1;
+        queue.push_back(start);
+        while let Some(u) = queue.pop_front() {
+            for &v in &neighbors[u] {
+                if !visited[v] {
+                    visited[v] = true;
+                    depth[v] = depth[u] + 1;
+                    children[u].push(v);
+                    queue.push_back(v);
+                }
+            }
+        }
+        match (0..n).filter(|&v| !visited[v]).max_by(by_degree) {
+            Some(next) => component_roots.push(next),
+            None => break,
+        }
+    }
+
+    let along = tidy_offsets(&children, n, config.sibling_spacing.max(1.0));
+    let (rx, ry) = input.positions[root];
+    let single = component_roots.len() == 1;
+    match config.style {
+        TreeStyle::Tidy => {
+            let root_x = along[root];
+            (0..n)
+                .map(|v| {
+                    let x = rx + along[v] - root_x;
+                    let y = ry + (depth[v] - 1) as f32 * config.level_spacing;
+                    (x, y)
+                })
+                .collect()
+        }
+        TreeStyle::Radial => {
+            // Spread the tidy order over the full circle, leaving one sibling
+            // gap so the first and last subtrees do not touch
+            let min = along[..n].iter().copied().fold(f32::INFINITY, f32::min);
+            let max = along[..n].iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let span = max - min + config.sibling_spacing.max(1.0);
+            (0..n)
+                .map(|v| {
+                    // With a single tree the root is the centre
+                    let ring = if single { depth[v] - 1 } else { depth[v] };
+                    let radius = ring as f32 * config.level_spacing;
+                    let angle = TAU * (along[v] - min) / span;
+                    (rx + radius * angle.cos(), ry + radius * angle.sin())
+                })
+                .collect()
+        }
+    }
+}
+
+// Left and right outline of a laid-out subtree, one entry per depth below its
+// root. Stored values plus `shift` give offsets from the subtree root.
+struct Contour {
+    left: VecDeque<f32>,
+    right: VecDeque<f32>,
+    shift: f32,
+}
+
+impl Contour {
+    fn leaf() -> Self {
+        Self {
+            left: VecDeque::from([0.0]),
+            right: VecDeque::from([0.0]),
+            shift: 0.0,
+        }
+    }
+}
+
+// Reingold-Tilford: place each node's subtrees left to right as close as their
+// contours allow, then centre the node over its first and last child. Returns
+// every node's horizontal position relative to `root`.
+fn tidy_offsets(children: &[Vec<usize>], root: usize, spacing: f32) -> Vec<f32> {
+    let count = children.len();
+    // Post-order without recursion so deep trees (long paths) cannot overflow
+    let mut order = Vec::with_capacity(count);
+    let mut stack = vec![root];
+    while let Some(v) = stack.pop() {
+        order.push(v);
+        stack.extend(&children[v]);
+    }
+
+    let mut relative = vec![0.0f32; count];
+    let mut contours: Vec<Option<Contour>> = (0..count).map(|_| None).collect();
+    for &v in order.iter().rev() {
+        let mut merged: Option<Contour> = None;
+        let mut placed: Vec<f32> = Vec::with_capacity(children[v].len());
+        for &c in &children[v] {
+            let child = contours[c].take().unwrap_or_else(Contour::leaf);
+            let Some(acc) = merged.as_mut() else {
+                placed.push(0.0);
+                merged = Some(child);
+                continue;
+            };
+            // Smallest offset keeping every shared depth `spacing` apart
+            let common = acc.right.len().min(child.left.len());
+            let offset = (0..common)
+                .map(|d| acc.right[d] + acc.shift - (child.left[d] + child.shift) + spacing)
+                .fold(f32::NEG_INFINITY, f32::max);
+            placed.push(offset);
+            for d in 0..common {
+                acc.right[d] = child.right[d] + child.shift + offset - acc.shift;
+            }
+            for d in common..child.left.len() {
+                acc.left
+                    .push_back(child.left[d] + child.shift + offset - acc.shift);
+                acc.right
+                    .push_back(child.right[d] + child.shift + offset - acc.shift);
+            }
+        }
+
+        let contour = match merged {
+            Some(mut acc) => {
+                let mid = (placed[0] + placed[placed.len() - 1]) * 0.5;
+                for (&c, &offset) in children[v].iter().zip(&placed) {
+                    relative[c] = offset - mid;
+                }
+                acc.shift -= mid;
+                acc.left.push_front(-acc.shift);
+                acc.right.push_front(-acc.shift);
+                acc
+            }
+            None => Contour::leaf(),
+        };
+        contours[v] = Some(contour);
+    }
+
+    let mut along = vec![0.0f32; count];
+    for &v in &order {
+        for &c in &children[v] {
+            along[c] = along[v] + relative[c];
+        }
+    }
+    along
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::GpugEdge;
+
+    #[test]
+    fn tidy_rows_do_not_overlap() {
+        // A complete binary tree plus a lopsided branch and a second component
+        let mut edges: Vec<GpugEdge> = (1..15).map(|i| GpugEdge::new((i - 1) / 2, i)).collect();
+        edges.extend((15..19).map(|i| GpugEdge::new(i - 1, i)));
+        edges.push(GpugEdge::new(19, 20));
+        let positions = vec![(100.0, 50.0); 21];
+        let input = LayoutInput {
+            positions: &positions,
+            edges: &edges,
+            pinned: &[],
+            radii: &[],
+        };
+        let config = TreeConfig {
+            root: Some(0),
+            ..TreeConfig::default()
+        };
+        let result = tree_layout(&input, &config);
+        assert_eq!(result[0], (100.0, 50.0));
+        for (i, a) in result.iter().enumerate() {
+            for b in &result[i + 1..] {
+                if a.1 == b.1 {
+                    let gap = (a.0 - b.0).abs();
+                    assert!(
+                        gap >= config.sibling_spacing - 1e-3,
+                        "gap {gap} on row {}",
+                        a.1
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use std::collections::HashMap;
+
 use gpui::div;
 use gpui::*;
 
+use crate::layout::snap_to_grid;
+
+pub const DEFAULT_NODE_RADIUS: f32 = 8.0;
+
 // Simple draggable node
 pub struct GpugNode {
     pub id: u64,
@@ -11,14 +17,87 @@ pub struct GpugNode {
     pub zoom: f32,
     pub pan: Point<Pixels>,
     pub selected: bool,
+    // Held in place by the force simulation
+    pub pinned: bool,
+    // Radius of the node's disc in world units
+    pub radius: f32,
+    // Free-form key/value data layouts can group or order nodes by
+    pub attributes: HashMap<String, String>,
+    // Grid spacing the position snaps to while dragged; None drags freely
+    pub snap_to_grid: Option<f32>,
+}
+
+impl GpugNode {
+    // Unselected, unpinned node of the default radius at world position (x, y)
+    pub fn new(id: u64, x: f32, y: f32) -> Self {
+        Self {
+            id,
+            x: px(x),
+            y: px(y),
+            drag_offset: None,
+            zoom: 1.0,
+            pan: point(px(0.0), px(0.0)),
+            selected: false,
+            pinned: false,
+            radius: DEFAULT_NODE_RADIUS,
+            attributes: HashMap::new(),
+            snap_to_grid: None,
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    pub fn with_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_attribute(key, value);
+        self
+    }
+
+    pub fn with_snap_to_grid(mut self, spacing: f32) -> Self {
+        self.snap_to_grid = Some(spacing);
+        self
+    }
+
+    // World-space position as plain floats, as used by the simulation and layouts
+    pub fn position(&self) -> (f32, f32) {
+        ((self.x / px(1.0)) as f32, (self.y / px(1.0)) as f32)
+    }
+
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.x = px(x);
+        self.y = px(y);
+    }
+
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
+    pub fn set_attribute(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.attributes.insert(key.into(), value.into());
+    }
+
+    fn end_drag(&mut self, _event: &MouseUpEvent, _window: &mut Window, _cx: &mut Context<Self>) {
+        self.drag_offset = None;
+    }
 }
 
 impl Render for GpugNode {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let node = div()
-            .size(px(16.0 * self.zoom))
+            .size(px(self.radius * 2.0 * self.zoom))
             .rounded_full()
-            .bg(rgb(0x000000))
+            .bg(if self.pinned {
+                rgb(0xB22222)
+            } else {
+                rgb(0x000000)
+            })
             .cursor_move()
             .id(("node", self.id as usize))
             // Start a drag with this node's id as payload; lets listeners filter events
@@ -41,21 +120,26 @@ impl Render for GpugNode {
                     }
 
                     if let Some(offset) = this.drag_offset {
-                        let new_origin = point(
+                        let mut new_origin = point(
                             (event.event.position.x - this.pan.x) / this.zoom - offset.x,
                             (event.event.position.y - this.pan.y) / this.zoom - offset.y,
                         );
+                        if let Some(spacing) = this.snap_to_grid.filter(|&s| s > 0.0) {
+                            let x = (new_origin.x / px(1.0)) as f32;
+                            let y = (new_origin.y / px(1.0)) as f32;
+                            new_origin =
+                                point(px(snap_to_grid(x, spacing)), px(snap_to_grid(y, spacing)));
+                        }
                         this.x = new_origin.x;
                         this.y = new_origin.y;
                         // position changes trigger re-render
                     }
                 }),
             )
-            .on_drop(cx.listener(|this, dragged_id: &u64, _window, _cx| {
-                if *dragged_id == this.id {
-                    this.drag_offset = None;
-                }
-            }));
+            // End the drag on any release rather than on drop: a node snapped
+            // to the grid can sit away from the cursor, so the release may miss it
+            .on_mouse_up(MouseButton::Left, cx.listener(Self::end_drag))
+            .on_mouse_up_out(MouseButton::Left, cx.listener(Self::end_drag));
 
         if self.selected {
             // Wrap the dot with a positioned container that provides
@@ -0,0 +1,104 @@
+// How node-node repulsion is approximated each step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepulsionStrategy {
+    // Only nodes in neighbouring grid cells repel; cheap but short-ranged
+    Grid,
+    // Quadtree approximation of all-pairs repulsion, O(n log n)
+    BarnesHut,
+}
+
+// How gravity pulls the layout toward `ForceSimulation::center`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CenteringMode {
+    // Every node is pulled toward the centre, compacting the layout
+    Pull,
+    // The whole layout is translated so its centroid moves to the centre,
+    // leaving relative positions untouched
+    Translate,
+}
+
+use crate::simulation::force_atlas2::ForceAtlas2Config;
+
+// Which physics drives the simulation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForceModel {
+    // Registered `Force`s integrated with velocities and alpha cooling
+    Classic,
+    // Gephi's ForceAtlas2 with its own adaptive speed, see `force_atlas2`.
+    // Registered `Force`s, custom ones included, are not applied.
+    ForceAtlas2,
+}
+
+// Tunable physics parameters for the force simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForceConfig {
+    pub model: ForceModel,
+    // Used instead of the classic forces when `model` is `ForceAtlas2`
+    pub force_atlas2: ForceAtlas2Config,
+    // Strength of node-node repulsion
+    pub repulsion: f32,
+    // Spring constant applied along edges, scaled by each edge's strength
+    pub attraction: f32,
+    // Rest length of edges that do not set their own
+    pub link_distance: f32,
+    // Pull of every node toward the simulation's centre
+    pub gravity: f32,
+    pub centering: CenteringMode,
+    // Fraction of each node's velocity kept from one step to the next
+    pub damping: f32,
+    // Integration time step
+    pub dt: f32,
+    // Cap on how far a node may move in one step
+    pub max_disp: f32,
+    // How much of a disc overlap is resolved per collision pass, in [0, 1]
+    pub collision_strength: f32,
+    pub collision_iterations: usize,
+    // Extra gap kept between the edges of neighbouring discs
+    pub collision_padding: f32,
+    // Passes projecting positions onto the simulation's constraints after each
+    // step; more settle constraints sharing nodes more exactly
+    pub constraint_iterations: usize,
+    // Cooling schedule: alpha scales all forces and eases toward
+    // `alpha_target` by `alpha_decay` each step; below `alpha_min` the
+    // layout is considered cold
+    pub alpha_min: f32,
+    pub alpha_decay: f32,
+    pub alpha_target: f32,
+    // The layout counts as converged once no node moved further than this
+    // in the last step
+    pub convergence_threshold: f32,
+    pub repulsion_strategy: RepulsionStrategy,
+    // Side length of the spatial grid cells used by `RepulsionStrategy::Grid`
+    pub cell_size: f32,
+    // Barnes-Hut opening angle; larger is faster but less accurate
+    pub theta: f32,
+}
+
+impl Default for ForceConfig {
+    fn default() -> Self {
+        Self {
+            model: ForceModel::Classic,
+            force_atlas2: ForceAtlas2Config::default(),
+            repulsion: 120.0,
+            attraction: 0.03,
+            link_distance: 0.0,
+            gravity: 0.006,
+            centering: CenteringMode::Pull,
+            damping: 0.6,
+            dt: 0.5,
+            max_disp: 5.0,
+            collision_strength: 0.7,
+            collision_iterations: 1,
+            collision_padding: 2.0,
+            constraint_iterations: 4,
+            // Cools from 1.0 to alpha_min in roughly 300 steps
+            alpha_min: 0.001,
+            alpha_decay: 0.0228,
+            alpha_target: 0.0,
+            convergence_threshold: 0.05,
+            repulsion_strategy: RepulsionStrategy::Grid,
+            cell_size: 100.0,
+            theta: 0.9,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// Which coordinate a constraint acts on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+// A geometric requirement on node centres. Rather than adding a force, layouts
+// project positions back onto it after every step, so it holds exactly.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constraint {
+    // The nodes share a coordinate: `Axis::Y` lines them up in a row,
+    // `Axis::X` in a column
+    Align {
+        axis: Axis,
+        nodes: Vec<usize>,
+    },
+    // `after` lies at least `gap` beyond `before` along `axis`; with `Axis::X`
+    // that is "before is left of after by at least gap"
+    Separate {
+        axis: Axis,
+        before: usize,
+        after: usize,
+        gap: f32,
+    },
+    // The nodes' discs stay inside the rectangle from `min` to `max`
+    Contain {
+        nodes: Vec<usize>,
+        min: (f32, f32),
+        max: (f32, f32),
+    },
+}
+
+impl Constraint {
+    // Whether the constraint refers to fixed canvas coordinates, so moving
+    // the whole layout can break it
+    pub fn is_absolute(&self) -> bool {
+        matches!(self, Constraint::Contain { .. })
+    }
+}
+
+// Move unpinned nodes the least needed to satisfy each constraint in turn,
+// sweeping `iterations` times so constraints sharing nodes settle together.
+// Positions are top-left corners with centres `radii` further on, as in the
+// force simulation; missing radii count as zero and out-of-range nodes are
+// ignored.
+pub fn project_constraints(
+    constraints: &[Constraint],
+    xs: &mut [f32],
+    ys: &mut [f32],
+    radii: &[f32],
+    pinned: &[bool],
+    iterations: usize,
+) {
+    let n = xs.len().min(ys.len());
+    let radius = |i: usize| radii.get(i).copied().unwrap_or(0.0);
+    let is_pinned = |i: usize| pinned.get(i).copied().unwrap_or(false);
+    for _ in 0..iterations {
+        for constraint in constraints {
+            match constraint {
+                Constraint::Align { axis, nodes } => {
+                    let coords = match axis {
+                        Axis::X => &mut *xs,
+                        Axis::Y => &mut *ys,
+                    };
+                    // Pinned members decide the line when there are any
+                    let (mut free, mut fixed) = ((0.0f32, 0.0f32), (0.0f32, 0.0f32));
+                    for &i in nodes.iter().filter(|&&i| i < n) {
+                        let sum = if is_pinned(i) { &mut fixed } else { &mut free };
+                        sum.0 += coords[i] + radius(i);
+                        sum.1 += 1.0;
+                    }
+                    let (total, count) = if fixed.1 > 0.0 { fixed } else { free };
+                    if count == 0.0 {
+                        continue;
+                    }
+                    let target = total / count;
+                    for &i in nodes.iter().filter(|&&i| i < n && !is_pinned(i)) {
+                        coords[i] = target - radius(i);
+                    }
+                }
+                &Constraint::Separate {
+                    axis,
+                    before,
+                    after,
+                    gap,
+                } => {
+                    if before >= n || after >= n || before == after {
+                        continue;
+                    }
+                    let coords = match axis {
+                        Axis::X => &mut *xs,
+                        Axis::Y => &mut *ys,
+                    };
+                    let shortfall =
+                        coords[before] + radius(before) + gap - coords[after] - radius(after);
+                    if shortfall <= 0.0 {
+                        continue;
+                    }
+                    // Split the correction, all of it going to the free node
+                    // when the other is pinned
+                    let (wb, wa) = match (is_pinned(before), is_pinned(after)) {
+                        (true, true) => continue,
+                        (true, false) => (0.0, 1.0),
+                        (false, true) => (1.0, 0.0),
+                        (false, false) => (0.5, 0.5),
+                    };
+                    coords[before] -= shortfall * wb;
+                    coords[after] += shortfall * wa;
+                }
+                Constraint::Contain { nodes, min, max } => {
+                    for &i in nodes.iter().filter(|&&i| i < n && !is_pinned(i)) {
+                        let r = radius(i);
+                        xs[i] = clamp_within(xs[i] + r, min.0 + r, max.0 - r) - r;
+                        ys[i] = clamp_within(ys[i] + r, min.1 + r, max.1 - r) - r;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// `value` clamped to [low, high], or their midpoint when the range is empty
+fn clamp_within(value: f32, low: f32, high: f32) -> f32 {
+    if low > high {
+        (low + high) / 2.0
+    } else {
+        value.clamp(low, high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RADIUS: f32 = 5.0;
+
+    fn project(
+        constraints: &[Constraint],
+        positions: &[(f32, f32)],
+        pinned: &[bool],
+    ) -> Vec<(f32, f32)> {
+        let mut xs: Vec<f32> = positions.iter().map(|p| p.0).collect();
+        let mut ys: Vec<f32> = positions.iter().map(|p| p.1).collect();
+        let radii = vec![RADIUS; positions.len()];
+        project_constraints(constraints, &mut xs, &mut ys, &radii, pinned, 10);
+        xs.into_iter().zip(ys).collect()
+    }
+
+    #[test]
+    fn projected_positions_satisfy_each_constraint() {
+        let positions = [(0.0, 0.0), (40.0, 90.0), (10.0, 30.0), (300.0, -50.0)];
+        let constraints = [
+            Constraint::Align {
+                axis: Axis::Y,
+                nodes: vec![0, 1],
+            },
+            Constraint::Separate {
+                axis: Axis::X,
+                before: 1,
+                after: 2,
+                gap: 50.0,
+            },
+            Constraint::Contain {
+                nodes: vec![3],
+                min: (0.0, 0.0),
+                max: (100.0, 100.0),
+            },
+        ];
+        let result = project(&constraints, &positions, &[]);
+
+        assert!((result[0].1 - result[1].1).abs() < 1e-3);
+        assert!(result[2].0 - result[1].0 >= 50.0 - 1e-3);
+        let (x, y) = result[3];
+        assert!(x >= 0.0 && y >= 0.0 && x + 2.0 * RADIUS <= 100.0 && y + 2.0 * RADIUS <= 100.0);
+    }
+
+    #[test]
+    fn pinned_nodes_keep_their_position() {
+        let positions = [(0.0, 0.0), (40.0, 90.0), (10.0, 30.0)];
+        let constraints = [
+            Constraint::Align {
+                axis: Axis::Y,
+                nodes: vec![0, 1],
+            },
+            Constraint::Separate {
+                axis: Axis::X,
+                before: 1,
+                after: 2,
+                gap: 50.0,
+            },
+        ];
+        let result = project(&constraints, &positions, &[false, true, false]);
+
+        assert_eq!(result[1], positions[1]);
+        assert!((result[0].1 - positions[1].1).abs() < 1e-3);
+        assert!(result[2].0 - positions[1].0 >= 50.0 - 1e-3);
+    }
+}
@@ -0,0 +1,460 @@
+use crate::edge::GpugEdge;
+use crate::simulation::quadtree::QuadTree;
+
+// Parameters of ForceAtlas2, named after their Gephi counterparts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForceAtlas2Config {
+    // Repulsion strength k_r; larger spreads the graph out
+    pub scaling_ratio: f32,
+    // Pull toward the centre, proportional to node mass
+    pub gravity: f32,
+    // Gravity grows with distance instead of being constant
+    pub strong_gravity: bool,
+    // Logarithmic attraction, which tightens clusters
+    pub lin_log: bool,
+    // Account for node radii so discs do not overlap
+    pub prevent_overlap: bool,
+    // Divide attraction by the source mass, pushing hubs to the periphery
+    pub dissuade_hubs: bool,
+    // Exponent applied to edge weights (`GpugEdge::strength`, default 1)
+    pub edge_weight_influence: f32,
+    // How much swinging is tolerated before the global speed drops
+    pub jitter_tolerance: f32,
+    // Approximate repulsion with a quadtree instead of all pairs
+    pub barnes_hut: bool,
+    pub barnes_hut_theta: f32,
+}
+
+impl Default for ForceAtlas2Config {
+    fn default() -> Self {
+        Self {
+            scaling_ratio: 2.0,
+            gravity: 1.0,
+            strong_gravity: false,
+            lin_log: false,
+            prevent_overlap: false,
+            dissuade_hubs: false,
+            edge_weight_influence: 1.0,
+            jitter_tolerance: 1.0,
+            barnes_hut: true,
+            barnes_hut_theta: 1.2,
+        }
+    }
+}
+
+// Adaptive speed carried from one step to the next, plus per-node buffers
+// kept so a step does not allocate
+pub struct ForceAtlas2State {
+    speed: f32,
+    speed_efficiency: f32,
+    old_fx: Vec<f32>,
+    old_fy: Vec<f32>,
+    masses: Vec<f32>,
+    cxs: Vec<f32>,
+    cys: Vec<f32>,
+    fx: Vec<f32>,
+    fy: Vec<f32>,
+    swinging: Vec<f32>,
+    tree: QuadTree,
+}
+
+impl Default for ForceAtlas2State {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            speed_efficiency: 1.0,
+            old_fx: Vec::new(),
+            old_fy: Vec::new(),
+            masses: Vec::new(),
+            cxs: Vec::new(),
+            cys: Vec::new(),
+            fx: Vec::new(),
+            fy: Vec::new(),
+            swinging: Vec::new(),
+            tree: QuadTree::new(),
+        }
+    }
+}
+
+impl ForceAtlas2State {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// Node state a ForceAtlas2 step reads and moves
+pub struct ForceAtlas2Nodes<'a> {
+    pub xs: &'a mut [f32],
+    pub ys: &'a mut [f32],
+    pub radii: &'a [f32],
+    pub pinned: &'a [bool],
+}
+
+// One ForceAtlas2 iteration (Jacomy et al. 2014) pulling toward `center`,
+// which like the classic gravity's is a target for top-left corners; returns
+// the kinetic energy (sum of 0.5 * |step|^2) and the largest step
+pub fn force_atlas2_step(
+    nodes: ForceAtlas2Nodes,
+    edges: &[GpugEdge],
+    config: &ForceAtlas2Config,
+    center: (f32, f32),
+    state: &mut ForceAtlas2State,
+) -> (f32, f32) {
+    let ForceAtlas2Nodes {
+        xs,
+        ys,
+        radii,
+        pinned,
+    } = nodes;
+    let n = xs.len();
+    if n == 0 {
+        return (0.0, 0.0);
+    }
+    let ForceAtlas2State {
+        speed,
+        speed_efficiency,
+        old_fx,
+        old_fy,
+        masses,
+        cxs,
+        cys,
+        fx,
+        fy,
+        swinging,
+        tree,
+    } = state;
+    if old_fx.len() != n {
+        refill(old_fx, n, 0.0);
+        refill(old_fy, n, 0.0);
+    }
+    refill(fx, n, 0.0);
+    refill(fy, n, 0.0);
+
+    // Mass is degree + 1 so leaves still repel
+    refill(masses, n, 1.0);
+    for edge in edges {
+        if edge.source < n && edge.target < n && edge.source != edge.target {
+            masses[edge.source] += 1.0;
+            masses[edge.target] += 1.0;
+        }
+    }
+    // Forces act between disc centres; positions are top-left corners
+    let radius = |i: usize| radii.get(i).copied().unwrap_or(0.0);
+    cxs.clear();
+    cxs.extend((0..n).map(|i| xs[i] + radius(i)));
+    cys.clear();
+    cys.extend((0..n).map(|i| ys[i] + radius(i)));
+
+    let k_r = config.scaling_ratio;
+
+    // Repulsion k_r * m_i * m_j / d, or / (d - r_i - r_j) with overlap prevention
+    let repel = |d: f32, gap: f32, mm: f32| -> f32 {
+        if !config.prevent_overlap {
+            return k_r * mm / d.max(0.01);
+        }
+        let d = d - gap;
+        if d > 0.0 {
+            k_r * mm / d
+        } else {
+            // Overlapping discs push apart hard
+            100.0 * k_r * mm
+        }
+    };
+    if config.barnes_hut {
+        // Cells have no single radius, so assume the average neighbour size
+        let mean_radius = (0..n).map(radius).sum::<f32>() / n as f32;
+        tree.rebuild_with_masses(cxs, cys, masses);
+        for i in 0..n {
+            let mi = masses[i];
+            let gap = radius(i) + mean_radius;
+            tree.visit(i, cxs[i], cys[i], config.barnes_hut_theta, |dx, dy, m| {
+                let d = (dx * dx + dy * dy).sqrt().max(0.01);
+                let f = repel(d, gap, mi * m) / d;
+                fx[i] -= dx * f;
+                fy[i] -= dy * f;
+            });
+        }
+    } else {
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let dx = cxs[j] - cxs[i];
+                let dy = cys[j] - cys[i];
+                let d = (dx * dx + dy * dy).sqrt().max(0.01);
+                let f = repel(d, radius(i) + radius(j), masses[i] * masses[j]) / d;
+                fx[i] -= dx * f;
+                fy[i] -= dy * f;
+                fx[j] += dx * f;
+                fy[j] += dy * f;
+            }
+        }
+    }
+
+    // Gravity, constant magnitude k_g * m unless strong
+    for i in 0..n {
+        let dx = center.0 - xs[i];
+        let dy = center.1 - ys[i];
+        let d = (dx * dx + dy * dy).sqrt();
+        if d <= 0.0 {
+            continue;
+        }
+        let f = if config.strong_gravity {
+            config.gravity * masses[i]
+        } else {
+            config.gravity * masses[i] / d
+        };
+        fx[i] += dx * f;
+        fy[i] += dy * f;
+    }
+
+    // Attraction along edges: linear in d, or log(1 + d) in LinLog mode
+    let hub_compensation = if config.dissuade_hubs {
+        masses.iter().sum::<f32>() / n as f32
+    } else {
+        1.0
+    };
+    for edge in edges {
+        let (i, j) = (edge.source, edge.target);
+        if i >= n || j >= n || i == j {
+            continue;
+        }
+        let dx = cxs[j] - cxs[i];
+        let dy = cys[j] - cys[i];
+        let mut d = (dx * dx + dy * dy).sqrt();
+        if config.prevent_overlap {
+            d -= radius(i) + radius(j);
+            if d <= 0.0 {
+                continue;
+            }
+        }
+        if d <= 0.0 {
+            continue;
+        }
+        let weight = edge
+            .strength
+            .unwrap_or(1.0)
+            .max(0.0)
+            .powf(config.edge_weight_influence);
+        let mut coefficient = weight;
+        if config.dissuade_hubs {
+            coefficient *= hub_compensation / masses[i];
+        }
+        // Force magnitude over the full centre distance gives a per-axis factor
+        let magnitude = if config.lin_log {
+            coefficient * (1.0 + d).ln()
+        } else {
+            coefficient * d
+        };
+        let f = magnitude / (dx * dx + dy * dy).sqrt().max(0.01);
+        fx[i] += dx * f;
+        fy[i] += dy * f;
+        fx[j] -= dx * f;
+        fy[j] -= dy * f;
+    }
+
+    // Adaptive global speed from total swinging vs. traction
+    let mut swinging_total = 0.0f32;
+    let mut traction_total = 0.0f32;
+    refill(swinging, n, 0.0);
+    for i in 0..n {
+        let sx = fx[i] - old_fx[i];
+        let sy = fy[i] - old_fy[i];
+        swinging[i] = masses[i] * (sx * sx + sy * sy).sqrt();
+        let tx = fx[i] + old_fx[i];
+        let ty = fy[i] + old_fy[i];
+        swinging_total += swinging[i];
+        traction_total += 0.5 * masses[i] * (tx * tx + ty * ty).sqrt();
+    }
+    let nf = n as f32;
+    let estimated_jitter = 0.05 * nf.sqrt();
+    let min_jitter = estimated_jitter.sqrt();
+    let max_jitter = 10.0f32;
+    let mut jitter = config.jitter_tolerance
+        * min_jitter.max(max_jitter.min(estimated_jitter * traction_total / (nf * nf)));
+    let min_efficiency = 0.05f32;
+    if traction_total > 0.0 && swinging_total / traction_total > 2.0 {
+        if *speed_efficiency > min_efficiency {
+            *speed_efficiency *= 0.5;
+        }
+        jitter = jitter.max(config.jitter_tolerance);
+    }
+    let target_speed = if swinging_total > 0.0 {
+        jitter * *speed_efficiency * traction_total / swinging_total
+    } else {
+        *speed
+    };
+    if swinging_total > jitter * traction_total {
+        if *speed_efficiency > min_efficiency {
+            *speed_efficiency *= 0.7;
+        }
+    } else if *speed < 1000.0 {
+        *speed_efficiency *= 1.3;
+    }
+    let max_rise = 0.5f32;
+    *speed += (target_speed - *speed).min(max_rise * *speed);
+
+    // Move each node by its force, slowed where it swings
+    let mut energy = 0.0f32;
+    let mut max_disp2 = 0.0f32;
+    for i in 0..n {
+        old_fx[i] = fx[i];
+        old_fy[i] = fy[i];
+        if pinned.get(i).copied().unwrap_or(false) {
+            continue;
+        }
+        let mut factor = *speed / (1.0 + (*speed * swinging[i]).sqrt());
+        if config.prevent_overlap {
+            factor *= 0.1;
+            let f = (fx[i] * fx[i] + fy[i] * fy[i]).sqrt();
+            if f > 0.0 {
+                factor = factor.min(10.0 / f);
+            }
+        }
+        let dx = fx[i] * factor;
+        let dy = fy[i] * factor;
+        xs[i] += dx;
+        ys[i] += dy;
+        let disp2 = dx * dx + dy * dy;
+        energy += 0.5 * disp2;
+        max_disp2 = max_disp2.max(disp2);
+    }
+    (energy, max_disp2.sqrt())
+}
+
+// Empty `buffer` and fill it with `n` copies of `value`, keeping its capacity
+fn refill(buffer: &mut Vec<f32>, n: usize, value: f32) {
+    buffer.clear();
+    buffer.resize(n, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two 4-cliques joined by a single bridge edge
+    fn barbell() -> Vec<GpugEdge> {
+        let mut edges = Vec::new();
+        for offset in [0, 4] {
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    edges.push(GpugEdge::new(offset + i, offset + j));
+                }
+            }
+        }
+        edges.push(GpugEdge::new(3, 4));
+        edges
+    }
+
+    fn run(
+        n: usize,
+        edges: &[GpugEdge],
+        config: &ForceAtlas2Config,
+        steps: usize,
+    ) -> Vec<(f32, f32)> {
+        let mut xs: Vec<f32> = (0..n).map(|i| (i * 37 % 100) as f32).collect();
+        let mut ys: Vec<f32> = (0..n).map(|i| (i * 61 % 100) as f32).collect();
+        let radii = vec![4.0; n];
+        let mut state = ForceAtlas2State::default();
+        for _ in 0..steps {
+            let nodes = ForceAtlas2Nodes {
+                xs: &mut xs,
+                ys: &mut ys,
+                radii: &radii,
+                pinned: &[],
+            };
+            force_atlas2_step(nodes, edges, config, (50.0, 50.0), &mut state);
+        }
+        xs.into_iter().zip(ys).collect()
+    }
+
+    fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    // Largest distance of any node from the gravity centre
+    fn extent(positions: &[(f32, f32)]) -> f32 {
+        positions
+            .iter()
+            .map(|&p| distance(p, (50.0, 50.0)))
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn small_graph_stays_bounded() {
+        let positions = run(8, &barbell(), &ForceAtlas2Config::default(), 500);
+        assert!(positions.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+        assert!(extent(&positions) < 1000.0, "extent {}", extent(&positions));
+    }
+
+    #[test]
+    fn connected_nodes_end_up_closer() {
+        let edges = barbell();
+        let positions = run(8, &edges, &ForceAtlas2Config::default(), 500);
+        let (mut linked, mut unlinked) = ((0.0, 0), (0.0, 0));
+        for i in 0..8 {
+            for j in (i + 1)..8 {
+                let is_edge = edges
+                    .iter()
+                    .any(|e| (e.source, e.target) == (i, j) || (e.source, e.target) == (j, i));
+                let sum = if is_edge { &mut linked } else { &mut unlinked };
+                sum.0 += distance(positions[i], positions[j]);
+                sum.1 += 1;
+            }
+        }
+        let linked = linked.0 / linked.1 as f32;
+        let unlinked = unlinked.0 / unlinked.1 as f32;
+        assert!(linked < unlinked, "linked {linked}, unlinked {unlinked}");
+    }
+
+    #[test]
+    fn strong_gravity_pulls_tighter_than_constant_gravity() {
+        let edges = barbell();
+        let constant = run(8, &edges, &ForceAtlas2Config::default(), 500);
+        let config = ForceAtlas2Config {
+            strong_gravity: true,
+            ..ForceAtlas2Config::default()
+        };
+        let strong = run(8, &edges, &config, 500);
+        assert!(strong.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+        assert!(
+            extent(&strong) < extent(&constant),
+            "strong {}, constant {}",
+            extent(&strong),
+            extent(&constant)
+        );
+    }
+
+    #[test]
+    fn buffers_are_reused_between_steps() {
+        let edges = barbell();
+        let mut xs: Vec<f32> = (0..8).map(|i| i as f32 * 10.0).collect();
+        let mut ys: Vec<f32> = (0..8).map(|i| (i * i) as f32).collect();
+        let mut state = ForceAtlas2State::default();
+        let config = ForceAtlas2Config::default();
+        let mut step = |state: &mut ForceAtlas2State| {
+            let nodes = ForceAtlas2Nodes {
+                xs: &mut xs,
+                ys: &mut ys,
+                radii: &[],
+                pinned: &[],
+            };
+            force_atlas2_step(nodes, &edges, &config, (0.0, 0.0), state);
+        };
+        step(&mut state);
+        let buffers = [
+            state.fx.as_ptr(),
+            state.old_fx.as_ptr(),
+            state.masses.as_ptr(),
+            state.cxs.as_ptr(),
+            state.swinging.as_ptr(),
+        ];
+        step(&mut state);
+        let reused = [
+            state.fx.as_ptr(),
+            state.old_fx.as_ptr(),
+            state.masses.as_ptr(),
+            state.cxs.as_ptr(),
+            state.swinging.as_ptr(),
+        ];
+        assert_eq!(buffers, reused);
+    }
+}
@@ -0,0 +1,657 @@
+use crate::edge::GpugEdge;
+use crate::simulation::config::{ForceConfig, ForceModel};
+use crate::simulation::constraints::{project_constraints, Constraint};
+use crate::simulation::force_atlas2::{force_atlas2_step, ForceAtlas2Nodes, ForceAtlas2State};
+use crate::simulation::forces::{default_forces, Force, ForceContext};
+use crate::simulation::spatial::SpatialGrid;
+
+// Headless force-directed layout; owns node positions in world space and
+// advances them one physics step at a time without needing a window
+pub struct ForceSimulation {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    vxs: Vec<f32>,
+    vys: Vec<f32>,
+    // Pinned nodes still exert forces but are never moved by a step
+    pinned: Vec<bool>,
+    // Disc radius of each node for collision; zero treats a node as a point
+    radii: Vec<f32>,
+    alpha: f32,
+    // World-space point gravity pulls toward
+    center: (f32, f32),
+    forces: Vec<Box<dyn Force>>,
+    // Enforced by projection after every step, see `project_constraints`
+    constraints: Vec<Constraint>,
+    // Force accumulators and collision bins, kept so a step does not allocate
+    fx: Vec<f32>,
+    fy: Vec<f32>,
+    collision_grid: SpatialGrid,
+    force_atlas2: ForceAtlas2State,
+    // Motion measured during the most recent step
+    kinetic_energy: f32,
+    max_displacement: f32,
+    edges: Vec<GpugEdge>,
+    config: ForceConfig,
+}
+
+impl ForceSimulation {
+    pub fn new(positions: Vec<(f32, f32)>, edges: Vec<GpugEdge>) -> Self {
+        Self::with_config(positions, edges, ForceConfig::default())
+    }
+
+    pub fn with_config(
+        positions: Vec<(f32, f32)>,
+        edges: Vec<GpugEdge>,
+        config: ForceConfig,
+    ) -> Self {
+        let (xs, ys): (Vec<f32>, Vec<f32>) = positions.into_iter().unzip();
+        let n = xs.len();
+        let mean = |values: &[f32]| values.iter().sum::<f32>() / n.max(1) as f32;
+        let center = (mean(&xs), mean(&ys));
+        let mut simulation = Self {
+            xs,
+            ys,
+            vxs: vec![0.0; n],
+            vys: vec![0.0; n],
+            pinned: vec![false; n],
+            radii: vec![0.0; n],
+            alpha: 1.0,
+            center,
+            kinetic_energy: f32::INFINITY,
+            max_displacement: f32::INFINITY,
+            forces: default_forces(),
+            constraints: Vec::new(),
+            fx: Vec::with_capacity(n),
+            fy: Vec::with_capacity(n),
+            collision_grid: SpatialGrid::new(),
+            force_atlas2: ForceAtlas2State::default(),
+            edges,
+            config,
+        };
+        simulation.initialize_forces();
+        simulation
+    }
+
+    pub fn config(&self) -> &ForceConfig {
+        &self.config
+    }
+
+    // Takes effect from the next step
+    pub fn set_config(&mut self, config: ForceConfig) {
+        self.config = config;
+    }
+
+    // Where gravity pulls; starts at the centroid of the initial positions
+    pub fn center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    pub fn set_center(&mut self, x: f32, y: f32) {
+        self.center = (x, y);
+    }
+
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.xs.iter().copied().zip(self.ys.iter().copied())
+    }
+
+    // Replace all positions, e.g. after nodes were dragged; velocities are
+    // kept unless the node count changes
+    pub fn set_positions(&mut self, positions: &[(f32, f32)]) {
+        self.xs.clear();
+        self.ys.clear();
+        for &(x, y) in positions {
+            self.xs.push(x);
+            self.ys.push(y);
+        }
+        if self.vxs.len() != positions.len() {
+            self.vxs = vec![0.0; positions.len()];
+            self.vys = vec![0.0; positions.len()];
+            self.pinned.resize(positions.len(), false);
+            self.radii.resize(positions.len(), 0.0);
+            self.force_atlas2.reset();
+            self.initialize_forces();
+        }
+    }
+
+    pub fn radius(&self, index: usize) -> f32 {
+        self.radii.get(index).copied().unwrap_or(0.0)
+    }
+
+    // Positions are the top-left corner of each node's bounding square, so
+    // its centre sits `radius` to the right of and below the position
+    pub fn set_radii(&mut self, radii: &[f32]) {
+        for i in 0..self.len() {
+            self.radii[i] = radii.get(i).copied().unwrap_or(0.0).max(0.0);
+        }
+    }
+
+    pub fn is_pinned(&self, index: usize) -> bool {
+        self.pinned.get(index).copied().unwrap_or(false)
+    }
+
+    // Pinning or releasing a node drops its momentum; setting the flag it
+    // already has leaves the node alone
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) {
+        if index >= self.len() || self.pinned[index] == pinned {
+            return;
+        }
+        self.pinned[index] = pinned;
+        self.vxs[index] = 0.0;
+        self.vys[index] = 0.0;
+    }
+
+    // Replace the pinned flag of every node at once; missing entries are unpinned
+    pub fn set_pinned_mask(&mut self, pinned: &[bool]) {
+        for i in 0..self.len() {
+            self.set_pinned(i, pinned.get(i).copied().unwrap_or(false));
+        }
+    }
+
+    // Move a single node and drop its momentum
+    pub fn set_position(&mut self, index: usize, x: f32, y: f32) {
+        if index >= self.len() {
+            return;
+        }
+        self.xs[index] = x;
+        self.ys[index] = y;
+        self.vxs[index] = 0.0;
+        self.vys[index] = 0.0;
+    }
+
+    pub fn velocities(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.vxs.iter().copied().zip(self.vys.iter().copied())
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha.max(0.0);
+    }
+
+    // Restart cooling from full temperature, e.g. after the graph was edited
+    pub fn reheat(&mut self) {
+        self.reheat_to(1.0);
+    }
+
+    // Raise alpha to at least `alpha` without cooling an already hot layout
+    pub fn reheat_to(&mut self, alpha: f32) {
+        self.alpha = self.alpha.max(alpha);
+        self.kinetic_energy = f32::INFINITY;
+        self.max_displacement = f32::INFINITY;
+    }
+
+    pub fn is_cold(&self) -> bool {
+        self.alpha < self.config.alpha_min
+    }
+
+    // Sum of 0.5 * |v|^2 over all nodes after the last step
+    pub fn kinetic_energy(&self) -> f32 {
+        self.kinetic_energy
+    }
+
+    // Largest distance any node moved in the last step
+    pub fn max_displacement(&self) -> f32 {
+        self.max_displacement
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.max_displacement < self.config.convergence_threshold
+    }
+
+    // Step until converged or `max_steps` is reached; returns the steps taken
+    pub fn run(&mut self, max_steps: usize) -> usize {
+        for steps in 0..max_steps {
+            if self.is_converged() {
+                return steps;
+            }
+            self.step();
+        }
+        max_steps
+    }
+
+    pub fn edges(&self) -> &[GpugEdge] {
+        &self.edges
+    }
+
+    pub fn set_edges(&mut self, edges: Vec<GpugEdge>) {
+        self.edges = edges;
+        self.initialize_forces();
+    }
+
+    // Names of the registered forces in the order they are applied
+    pub fn force_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.forces.iter().map(|force| force.name())
+    }
+
+    // Register a force to be applied after the existing ones; it has no effect
+    // while the model is `ForceModel::ForceAtlas2`
+    pub fn add_force(&mut self, mut force: Box<dyn Force>) {
+        force.initialize(&self.context());
+        self.forces.push(force);
+    }
+
+    // Unregister the first force called `name`, returning it
+    pub fn remove_force(&mut self, name: &str) -> Option<Box<dyn Force>> {
+        let index = self.forces.iter().position(|force| force.name() == name)?;
+        Some(self.forces.remove(index))
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    // Replace the constraints; they hold from the next step
+    pub fn set_constraints(&mut self, constraints: Vec<Constraint>) {
+        self.constraints = constraints;
+    }
+
+    fn context(&self) -> ForceContext<'_> {
+        ForceContext {
+            xs: &self.xs,
+            ys: &self.ys,
+            edges: &self.edges,
+            radii: &self.radii,
+            pinned: &self.pinned,
+            alpha: self.alpha,
+            center: self.center,
+            config: &self.config,
+        }
+    }
+
+    fn initialize_forces(&mut self) {
+        let mut forces = std::mem::take(&mut self.forces);
+        let cx = self.context();
+        for force in &mut forces {
+            force.initialize(&cx);
+        }
+        self.forces = forces;
+    }
+
+    // Advance the layout by a single physics step
+    pub fn step(&mut self) {
+        let n = self.len();
+        if n == 0 {
+            return;
+        }
+        let config = self.config;
+        self.alpha += (config.alpha_target - self.alpha) * config.alpha_decay;
+        let alpha = self.alpha;
+
+        if config.model == ForceModel::ForceAtlas2 {
+            let nodes = ForceAtlas2Nodes {
+                xs: &mut self.xs,
+                ys: &mut self.ys,
+                radii: &self.radii,
+                pinned: &self.pinned,
+            };
+            let (energy, max_disp) = force_atlas2_step(
+                nodes,
+                &self.edges,
+                &config.force_atlas2,
+                self.center,
+                &mut self.force_atlas2,
+            );
+            self.kinetic_energy = energy;
+            self.max_displacement = max_disp;
+            self.enforce_constraints(false);
+            return;
+        }
+
+        let mut fx = std::mem::take(&mut self.fx);
+        let mut fy = std::mem::take(&mut self.fy);
+        fx.clear();
+        fx.resize(n, 0.0);
+        fy.clear();
+        fy.resize(n, 0.0);
+        let mut forces = std::mem::take(&mut self.forces);
+        let cx = self.context();
+        for force in &mut forces {
+            force.apply(&cx, &mut fx, &mut fy);
+        }
+        self.forces = forces;
+
+        let ForceConfig {
+            damping,
+            dt,
+            max_disp,
+            ..
+        } = config;
+        let xs = &mut self.xs;
+        let ys = &mut self.ys;
+        let vxs = &mut self.vxs;
+        let vys = &mut self.vys;
+
+        // Integrate velocities scaled by the current temperature and clamp the step
+        let mut energy = 0.0f32;
+        let mut max_step2 = 0.0f32;
+        for i in 0..n {
+            if self.pinned[i] {
+                vxs[i] = 0.0;
+                vys[i] = 0.0;
+                continue;
+            }
+            let mut vx = vxs[i] * damping + fx[i] * dt * alpha;
+            let mut vy = vys[i] * damping + fy[i] * dt * alpha;
+            let v2 = vx * vx + vy * vy;
+            if v2 > max_disp * max_disp {
+                let s = max_disp / v2.sqrt();
+                vx *= s;
+                vy *= s;
+            }
+            vxs[i] = vx;
+            vys[i] = vy;
+            xs[i] += vx;
+            ys[i] += vy;
+            let step2 = vx * vx + vy * vy;
+            energy += 0.5 * step2;
+            max_step2 = max_step2.max(step2);
+        }
+        self.kinetic_energy = energy;
+        self.max_displacement = max_step2.sqrt();
+        self.fx = fx;
+        self.fy = fy;
+
+        for _ in 0..config.collision_iterations {
+            resolve_collisions(
+                xs,
+                ys,
+                &self.radii,
+                &self.pinned,
+                &mut self.collision_grid,
+                config.collision_strength,
+                config.collision_padding,
+            );
+        }
+        self.enforce_constraints(true);
+    }
+
+    // Project positions onto the constraints. With `correct_velocities` each
+    // node keeps only the motion that survived as its velocity, so nodes stop
+    // pushing into a constraint instead of building up speed against it.
+    fn enforce_constraints(&mut self, correct_velocities: bool) {
+        if self.constraints.is_empty() {
+            return;
+        }
+        // The force accumulators are free once a step has integrated them
+        let mut before_x = std::mem::take(&mut self.fx);
+        let mut before_y = std::mem::take(&mut self.fy);
+        before_x.clear();
+        before_x.extend_from_slice(&self.xs);
+        before_y.clear();
+        before_y.extend_from_slice(&self.ys);
+        project_constraints(
+            &self.constraints,
+            &mut self.xs,
+            &mut self.ys,
+            &self.radii,
+            &self.pinned,
+            self.config.constraint_iterations,
+        );
+
+        if correct_velocities {
+            let mut energy = 0.0f32;
+            let mut max_step2 = 0.0f32;
+            for i in 0..self.len() {
+                if self.pinned[i] {
+                    continue;
+                }
+                self.vxs[i] += self.xs[i] - before_x[i];
+                self.vys[i] += self.ys[i] - before_y[i];
+                let step2 = self.vxs[i] * self.vxs[i] + self.vys[i] * self.vys[i];
+                energy += 0.5 * step2;
+                max_step2 = max_step2.max(step2);
+            }
+            self.kinetic_energy = energy;
+            self.max_displacement = max_step2.sqrt();
+        }
+        self.fx = before_x;
+        self.fy = before_y;
+    }
+}
+
+// Push overlapping node discs apart along the line between their centres,
+// moving the smaller disc further; pinned nodes never move
+fn resolve_collisions(
+    xs: &mut [f32],
+    ys: &mut [f32],
+    radii: &[f32],
+    pinned: &[bool],
+    grid: &mut SpatialGrid,
+    strength: f32,
+    padding: f32,
+) {
+    let n = xs.len();
+    let max_radius = radii.iter().copied().fold(0.0f32, f32::max);
+    if max_radius <= 0.0 || strength <= 0.0 {
+        return;
+    }
+
+    // Any two overlapping discs sit in the same or adjacent cells
+    let cell = max_radius * 2.0 + padding.max(0.0);
+    let centre = |xs: &[f32], ys: &[f32], i: usize| (xs[i] + radii[i], ys[i] + radii[i]);
+    grid.rebuild(n, cell, |i| centre(xs, ys, i));
+
+    for i in 0..n {
+        grid.for_each_near(i, |j, _, _| {
+            if j <= i || (pinned[i] && pinned[j]) {
+                return;
+            }
+            let (ix, iy) = centre(xs, ys, i);
+            let (jx, jy) = centre(xs, ys, j);
+            let min_dist = radii[i] + radii[j] + padding;
+            let dx = jx - ix;
+            let dy = jy - iy;
+            let d2 = dx * dx + dy * dy;
+            if d2 >= min_dist * min_dist {
+                return;
+            }
+            let d = d2.sqrt();
+            let (ux, uy) = if d > 1e-6 {
+                (dx / d, dy / d)
+            } else {
+                // Coincident centres: separate along a fixed per-pair direction
+                let angle = (i * 31 + j) as f32;
+                (angle.cos(), angle.sin())
+            };
+            let overlap = (min_dist - d) * strength;
+
+            let ri2 = radii[i] * radii[i];
+            let rj2 = radii[j] * radii[j];
+            let mut wi = if ri2 + rj2 > 0.0 {
+                rj2 / (ri2 + rj2)
+            } else {
+                0.5
+            };
+            if pinned[i] {
+                wi = 0.0;
+            } else if pinned[j] {
+                wi = 1.0;
+            }
+            let wj = 1.0 - wi;
+
+            xs[i] -= ux * overlap * wi;
+            ys[i] -= uy * overlap * wi;
+            xs[j] += ux * overlap * wj;
+            ys[j] += uy * overlap * wj;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ring of `n` nodes scattered around the default gravity centre
+    fn ring(n: usize) -> ForceSimulation {
+        let positions = (0..n)
+            .map(|i| (700.0 + (i * 37 % 200) as f32, 100.0 + (i * 91 % 200) as f32))
+            .collect();
+        let edges = (0..n).map(|i| GpugEdge::new(i, (i + 1) % n)).collect();
+        ForceSimulation::new(positions, edges)
+    }
+
+    #[test]
+    fn small_graph_converges() {
+        let mut simulation = ring(8);
+        let steps = simulation.run(2000);
+        assert!(steps < 2000, "not converged after {steps} steps");
+        assert!(simulation.is_converged());
+        assert!(simulation
+            .positions()
+            .all(|(x, y)| x.is_finite() && y.is_finite()));
+    }
+
+    #[test]
+    fn pinned_nodes_do_not_move() {
+        let mut simulation = ring(8);
+        simulation.set_pinned(0, true);
+        let before = simulation.positions().next();
+        for _ in 0..100 {
+            simulation.step();
+        }
+        assert_eq!(simulation.positions().next(), before);
+        assert!(simulation
+            .positions()
+            .skip(1)
+            .ne(ring(8).positions().skip(1)));
+    }
+
+    #[test]
+    fn resending_the_pinned_mask_keeps_velocities() {
+        let mut simulation = ring(8);
+        let mut mask = vec![false; 8];
+        mask[0] = true;
+        simulation.set_pinned_mask(&mask);
+        for _ in 0..10 {
+            simulation.step();
+        }
+        let velocities: Vec<(f32, f32)> = simulation.velocities().collect();
+        simulation.set_pinned_mask(&mask);
+        assert!(simulation.velocities().eq(velocities.iter().copied()));
+        simulation.set_pinned(1, true);
+        assert_eq!(simulation.velocities().nth(1), Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn constraints_hold_after_each_step() {
+        use crate::simulation::constraints::Axis;
+
+        let mut simulation = ring(8);
+        simulation.set_constraints(vec![
+            Constraint::Align {
+                axis: Axis::Y,
+                nodes: vec![0, 2, 4],
+            },
+            Constraint::Contain {
+                nodes: vec![1],
+                min: (0.0, 0.0),
+                max: (100.0, 100.0),
+            },
+        ]);
+        for _ in 0..50 {
+            simulation.step();
+            let positions: Vec<(f32, f32)> = simulation.positions().collect();
+            assert!((positions[0].1 - positions[2].1).abs() < 1e-3);
+            assert!((positions[0].1 - positions[4].1).abs() < 1e-3);
+            let (x, y) = positions[1];
+            assert!((0.0..=100.0).contains(&x) && (0.0..=100.0).contains(&y));
+        }
+    }
+
+    #[test]
+    fn setting_constraints_does_not_reheat() {
+        let mut simulation = ring(8);
+        simulation.run(2000);
+        let alpha = simulation.alpha();
+        simulation.set_constraints(Vec::new());
+        assert_eq!(simulation.alpha(), alpha);
+        assert!(simulation.is_converged());
+    }
+
+    // Positions after ten steps of a six-node ring, recorded from the serial
+    // build; `--features parallel` must reproduce them
+    #[test]
+    fn parallel_steps_match_serial_steps() {
+        use crate::simulation::config::RepulsionStrategy;
+
+        let expected = [
+            (
+                RepulsionStrategy::Grid,
+                [
+                    (713.96, 100.22),
+                    (714.18, 192.29),
+                    (760.94, 281.36),
+                    (814.49, 188.27),
+                    (867.81, 256.9),
+                    (883.62, 145.97),
+                ],
+            ),
+            (
+                RepulsionStrategy::BarnesHut,
+                [
+                    (712.51, 100.44),
+                    (716.0, 192.22),
+                    (760.51, 281.32),
+                    (814.71, 188.39),
+                    (868.78, 259.74),
+                    (884.27, 145.7),
+                ],
+            ),
+        ];
+        for (strategy, positions) in expected {
+            let reference = ring(6);
+            let config = ForceConfig {
+                repulsion_strategy: strategy,
+                ..ForceConfig::default()
+            };
+            let mut simulation = ForceSimulation::with_config(
+                reference.positions().collect(),
+                reference.edges().to_vec(),
+                config,
+            );
+            simulation.set_radii(&[8.0; 6]);
+            for _ in 0..10 {
+                simulation.step();
+            }
+            for ((x, y), (ex, ey)) in simulation.positions().zip(positions) {
+                assert!(
+                    (x - ex).abs() < 0.01 && (y - ey).abs() < 0.01,
+                    "{strategy:?}: ({x}, {y}) instead of ({ex}, {ey})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn collisions_separate_overlapping_discs() {
+        // Two coincident discs, one overlapping them and a pinned one in the way
+        let mut xs = vec![0.0, 0.0, 5.0, -12.0];
+        let mut ys = vec![0.0, 0.0, 3.0, 0.0];
+        let radii = [8.0, 4.0, 10.0, 6.0];
+        let pinned = [false, false, false, true];
+        let mut grid = SpatialGrid::new();
+        for _ in 0..50 {
+            resolve_collisions(&mut xs, &mut ys, &radii, &pinned, &mut grid, 1.0, 0.0);
+        }
+
+        assert_eq!((xs[3], ys[3]), (-12.0, 0.0));
+        for i in 0..4 {
+            for j in (i + 1)..4 {
+                let dx = (xs[j] + radii[j]) - (xs[i] + radii[i]);
+                let dy = (ys[j] + radii[j]) - (ys[i] + radii[i]);
+                let d = (dx * dx + dy * dy).sqrt();
+                assert!(d >= radii[i] + radii[j] - 1e-2, "{i} and {j} {d} apart");
+            }
+        }
+    }
+}
@@ -0,0 +1,432 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::edge::GpugEdge;
+use crate::simulation::config::{CenteringMode, ForceConfig, RepulsionStrategy};
+use crate::simulation::quadtree::QuadTree;
+use crate::simulation::spatial::SpatialGrid;
+
+// Read-only view of the simulation state handed to every force
+pub struct ForceContext<'a> {
+    pub xs: &'a [f32],
+    pub ys: &'a [f32],
+    pub edges: &'a [GpugEdge],
+    pub radii: &'a [f32],
+    pub pinned: &'a [bool],
+    // Current temperature; the integrator already scales forces by it
+    pub alpha: f32,
+    // World-space point gravity pulls toward, see `ForceSimulation::center`
+    pub center: (f32, f32),
+    pub config: &'a ForceConfig,
+}
+
+impl ForceContext<'_> {
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+}
+
+// A force contributing to the layout; the simulation calls `apply` once per
+// step with the force accumulators for every node
+pub trait Force: Send {
+    // Used to look the force up when removing it from a simulation
+    fn name(&self) -> &str;
+
+    // Called when nodes or edges change so the force can rebuild cached data
+    fn initialize(&mut self, _cx: &ForceContext) {}
+
+    fn apply(&mut self, cx: &ForceContext, fx: &mut [f32], fy: &mut [f32]);
+}
+
+// Node-node repulsion using `ForceConfig::repulsion_strategy`
+#[derive(Default)]
+pub struct RepulsionForce {
+    // Reused between steps by `RepulsionStrategy::Grid` and `BarnesHut`
+    grid: SpatialGrid,
+    tree: QuadTree,
+}
+
+impl Force for RepulsionForce {
+    fn name(&self) -> &str {
+        "repulsion"
+    }
+
+    fn apply(&mut self, cx: &ForceContext, fx: &mut [f32], fy: &mut [f32]) {
+        let config = cx.config;
+        match config.repulsion_strategy {
+            RepulsionStrategy::Grid => grid_repulsion(
+                &mut self.grid,
+                cx.xs,
+                cx.ys,
+                fx,
+                fy,
+                config.repulsion,
+                config.cell_size,
+            ),
+            RepulsionStrategy::BarnesHut => barnes_hut_repulsion(
+                &mut self.tree,
+                cx.xs,
+                cx.ys,
+                fx,
+                fy,
+                config.repulsion,
+                config.theta,
+            ),
+        }
+    }
+}
+
+// Springs along edges pulling each pair toward its rest length
+#[derive(Default)]
+pub struct LinkForce {
+    // Per-edge spring strength and the share of the pull taken by the target
+    strengths: Vec<f32>,
+    biases: Vec<f32>,
+    // Edges incident to node i are incident[incident_starts[i]..incident_starts[i + 1]],
+    // letting each node gather its own pull without write conflicts
+    #[cfg(feature = "parallel")]
+    incident_starts: Vec<u32>,
+    #[cfg(feature = "parallel")]
+    incident: Vec<u32>,
+}
+
+impl LinkForce {
+    // Spring pull of edge `e` on its source, or None when the edge is skipped;
+    // the target receives the opposite direction scaled by the bias
+    fn pull(&self, cx: &ForceContext, e: usize) -> Option<(f32, f32)> {
+        let edge = &cx.edges[e];
+        let (i, j) = (edge.source, edge.target);
+        if i >= cx.len() || j >= cx.len() || i == j || e >= self.strengths.len() {
+            return None;
+        }
+        let dx = cx.xs[j] - cx.xs[i];
+        let dy = cx.ys[j] - cx.ys[i];
+        let d = (dx * dx + dy * dy).sqrt().max(1e-6);
+        let rest = edge.length.unwrap_or(cx.config.link_distance);
+        let k = cx.config.attraction * self.strengths[e] * (d - rest) / d;
+        // Split so that equal degrees give each end the full pull
+        Some((2.0 * k * dx, 2.0 * k * dy))
+    }
+
+    #[cfg(feature = "parallel")]
+    fn build_incidence(&mut self, cx: &ForceContext, degrees: &[usize]) {
+        let n = degrees.len();
+        self.incident_starts.clear();
+        self.incident_starts.push(0);
+        let mut start = 0u32;
+        for &degree in degrees {
+            start += degree as u32;
+            self.incident_starts.push(start);
+        }
+        let mut cursors = self.incident_starts[..n].to_vec();
+        self.incident.clear();
+        self.incident.resize(start as usize, 0);
+        for (e, edge) in cx.edges.iter().enumerate() {
+            if edge.source < n && edge.target < n {
+                for end in [edge.source, edge.target] {
+                    self.incident[cursors[end] as usize] = e as u32;
+                    cursors[end] += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Force for LinkForce {
+    fn name(&self) -> &str {
+        "link"
+    }
+
+    // Degree-normalised defaults as in d3-force: an edge's strength is the
+    // inverse of its lower endpoint degree, and the endpoint with the higher
+    // degree moves less
+    fn initialize(&mut self, cx: &ForceContext) {
+        let n = cx.len();
+        let mut degrees = vec![0usize; n];
+        for edge in cx.edges {
+            if edge.source < n && edge.target < n {
+                degrees[edge.source] += 1;
+                degrees[edge.target] += 1;
+            }
+        }
+        self.strengths.clear();
+        self.biases.clear();
+        for edge in cx.edges {
+            let ds = degrees.get(edge.source).copied().unwrap_or(1).max(1) as f32;
+            let dt = degrees.get(edge.target).copied().unwrap_or(1).max(1) as f32;
+            self.strengths
+                .push(edge.strength.unwrap_or(1.0 / ds.min(dt)));
+            self.biases.push(ds / (ds + dt));
+        }
+        #[cfg(feature = "parallel")]
+        self.build_incidence(cx, &degrees);
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn apply(&mut self, cx: &ForceContext, fx: &mut [f32], fy: &mut [f32]) {
+        for (e, edge) in cx.edges.iter().enumerate() {
+            let Some((px, py)) = self.pull(cx, e) else {
+                continue;
+            };
+            let (i, j) = (edge.source, edge.target);
+            let bias = self.biases[e];
+            fx[i] += px * (1.0 - bias);
+            fy[i] += py * (1.0 - bias);
+            fx[j] -= px * bias;
+            fy[j] -= py * bias;
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn apply(&mut self, cx: &ForceContext, fx: &mut [f32], fy: &mut [f32]) {
+        if self.incident_starts.len() != cx.len() + 1 {
+            return;
+        }
+        let this = &*self;
+        accumulate(fx, fy, |i| {
+            let range = this.incident_starts[i] as usize..this.incident_starts[i + 1] as usize;
+            let mut sum = (0.0, 0.0);
+            for &e in &this.incident[range] {
+                let e = e as usize;
+                let Some((px, py)) = this.pull(cx, e) else {
+                    continue;
+                };
+                let bias = this.biases[e];
+                // Self-loops are skipped by `pull`, so i is exactly one end
+                if cx.edges[e].source == i {
+                    sum.0 += px * (1.0 - bias);
+                    sum.1 += py * (1.0 - bias);
+                } else {
+                    sum.0 -= px * bias;
+                    sum.1 -= py * bias;
+                }
+            }
+            sum
+        });
+    }
+}
+
+// Pull toward `ForceContext::center`, per node or on the layout centroid
+// depending on `ForceConfig::centering`
+pub struct GravityForce;
+
+impl Force for GravityForce {
+    fn name(&self) -> &str {
+        "gravity"
+    }
+
+    fn apply(&mut self, cx: &ForceContext, fx: &mut [f32], fy: &mut [f32]) {
+        let gravity = cx.config.gravity;
+        let (center_x, center_y) = cx.center;
+        match cx.config.centering {
+            CenteringMode::Pull => {
+                for i in 0..cx.len() {
+                    fx[i] += gravity * (center_x - cx.xs[i]);
+                    fy[i] += gravity * (center_y - cx.ys[i]);
+                }
+            }
+            CenteringMode::Translate => {
+                if cx.is_empty() {
+                    return;
+                }
+                // The same force on every node moves the layout rigidly
+                let n = cx.len() as f32;
+                let mean_x = cx.xs.iter().sum::<f32>() / n;
+                let mean_y = cx.ys.iter().sum::<f32>() / n;
+                let gx = gravity * (center_x - mean_x);
+                let gy = gravity * (center_y - mean_y);
+                for i in 0..cx.len() {
+                    fx[i] += gx;
+                    fy[i] += gy;
+                }
+            }
+        }
+    }
+}
+
+// The forces a new simulation starts with
+pub fn default_forces() -> Vec<Box<dyn Force>> {
+    vec![
+        Box::new(RepulsionForce::default()),
+        Box::new(LinkForce::default()),
+        Box::new(GravityForce),
+    ]
+}
+
+// Add `f(i)` to the accumulators of every node; `f` only reads shared state,
+// so with the `parallel` feature nodes are spread across threads
+fn accumulate(fx: &mut [f32], fy: &mut [f32], f: impl Fn(usize) -> (f32, f32) + Sync) {
+    #[cfg(feature = "parallel")]
+    fx.par_iter_mut()
+        .zip(fy.par_iter_mut())
+        .enumerate()
+        .for_each(|(i, (x, y))| {
+            let (dx, dy) = f(i);
+            *x += dx;
+            *y += dy;
+        });
+    #[cfg(not(feature = "parallel"))]
+    for (i, (x, y)) in fx.iter_mut().zip(fy.iter_mut()).enumerate() {
+        let (dx, dy) = f(i);
+        *x += dx;
+        *y += dy;
+    }
+}
+
+// Repulsion between nodes in the same or adjacent cells of a uniform grid
+fn grid_repulsion(
+    grid: &mut SpatialGrid,
+    xs: &[f32],
+    ys: &[f32],
+    fx: &mut [f32],
+    fy: &mut [f32],
+    repulsion: f32,
+    cell: f32,
+) {
+    let n = xs.len();
+    grid.rebuild(n, cell.max(1.0), |i| (xs[i], ys[i]));
+    let push = |i: usize, x: f32, y: f32| {
+        let dx = x - xs[i];
+        let dy = y - ys[i];
+        let inv = 1.0 / (dx * dx + dy * dy + 0.01);
+        (repulsion * dx * inv, repulsion * dy * inv)
+    };
+
+    // Serially each pair is visited once and applied to both ends
+    #[cfg(not(feature = "parallel"))]
+    for &i in grid.order() {
+        let i = i as usize;
+        grid.for_each_near(i, |j, x, y| {
+            if j > i {
+                let (px, py) = push(i, x, y);
+                fx[i] -= px;
+                fy[i] -= py;
+                fx[j] += px;
+                fy[j] += py;
+            }
+        });
+    }
+
+    // In parallel every node gathers its own pushes, doing each pair twice
+    // but writing only to itself
+    #[cfg(feature = "parallel")]
+    {
+        let grid = &*grid;
+        accumulate(fx, fy, |i| {
+            let mut sum = (0.0, 0.0);
+            grid.for_each_near(i, |j, x, y| {
+                if j != i {
+                    let (px, py) = push(i, x, y);
+                    sum.0 -= px;
+                    sum.1 -= py;
+                }
+            });
+            sum
+        });
+    }
+}
+
+// Long-range repulsion using a Barnes-Hut quadtree, same force law as the grid
+fn barnes_hut_repulsion(
+    tree: &mut QuadTree,
+    xs: &[f32],
+    ys: &[f32],
+    fx: &mut [f32],
+    fy: &mut [f32],
+    repulsion: f32,
+    theta: f32,
+) {
+    tree.rebuild(xs, ys);
+    let tree = &*tree;
+    accumulate(fx, fy, |i| {
+        let mut sum = (0.0, 0.0);
+        tree.visit(i, xs[i], ys[i], theta, |dx, dy, mass| {
+            let inv = 1.0 / (dx * dx + dy * dy + 0.01);
+            sum.0 -= repulsion * mass * dx * inv;
+            sum.1 -= repulsion * mass * dy * inv;
+        });
+        sum
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::utils::rand_f32;
+
+    #[test]
+    fn barnes_hut_matches_exact_repulsion() {
+        let mut seed = 7;
+        let n = 300;
+        let xs: Vec<f32> = (0..n).map(|_| rand_f32(&mut seed) * 1000.0).collect();
+        let ys: Vec<f32> = (0..n).map(|_| rand_f32(&mut seed) * 1000.0).collect();
+        let exact: Vec<(f32, f32)> = (0..n)
+            .map(|i| {
+                (0..n).filter(|&j| j != i).fold((0.0, 0.0), |(sx, sy), j| {
+                    let dx = xs[j] - xs[i];
+                    let dy = ys[j] - ys[i];
+                    let inv = 1.0 / (dx * dx + dy * dy + 0.01);
+                    (sx - 100.0 * dx * inv, sy - 100.0 * dy * inv)
+                })
+            })
+            .collect();
+
+        // theta = 0 opens every cell, so only rounding differs; theta = 0.5
+        // approximates far cells and stays within a few percent
+        let mut tree = QuadTree::new();
+        for (theta, tolerance) in [(0.0, 1e-3), (0.5, 0.05)] {
+            let mut fx = vec![0.0; n];
+            let mut fy = vec![0.0; n];
+            barnes_hut_repulsion(&mut tree, &xs, &ys, &mut fx, &mut fy, 100.0, theta);
+            let (mut error, mut norm) = (0.0f32, 0.0f32);
+            for i in 0..n {
+                error += (fx[i] - exact[i].0).powi(2) + (fy[i] - exact[i].1).powi(2);
+                norm += exact[i].0.powi(2) + exact[i].1.powi(2);
+            }
+            let relative = (error / norm).sqrt();
+            assert!(
+                relative < tolerance,
+                "theta {theta}: relative error {relative}"
+            );
+        }
+    }
+
+    #[test]
+    fn link_force_honours_edge_overrides() {
+        let xs = [0.0, 120.0];
+        let ys = [0.0, 0.0];
+        let config = ForceConfig::default();
+        let pull = |edge: GpugEdge| {
+            let edges = [edge];
+            let cx = ForceContext {
+                xs: &xs,
+                ys: &ys,
+                edges: &edges,
+                radii: &[],
+                pinned: &[],
+                alpha: 1.0,
+                center: (0.0, 0.0),
+                config: &config,
+            };
+            let mut link = LinkForce::default();
+            link.initialize(&cx);
+            let mut fx = [0.0; 2];
+            let mut fy = [0.0; 2];
+            link.apply(&cx, &mut fx, &mut fy);
+            fx[0]
+        };
+
+        // At its rest length an edge pulls nothing, whatever `link_distance` is
+        assert_eq!(pull(GpugEdge::new(0, 1).with_length(120.0)), 0.0);
+        assert!(pull(GpugEdge::new(0, 1).with_length(60.0)) > 0.0);
+        assert!(pull(GpugEdge::new(0, 1).with_length(200.0)) < 0.0);
+        // Pull scales with strength
+        let single = pull(GpugEdge::new(0, 1).with_length(60.0).with_strength(1.0));
+        let double = pull(GpugEdge::new(0, 1).with_length(60.0).with_strength(2.0));
+        assert!((double - 2.0 * single).abs() < 1e-4 * single.abs());
+    }
+}
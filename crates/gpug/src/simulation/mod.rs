@@ -0,0 +1,8 @@
+pub mod config;
+pub mod constraints;
+pub mod force_atlas2;
+pub mod force_simulation;
+pub mod forces;
+pub mod quadtree;
+pub mod shared;
+pub mod spatial;
@@ -0,0 +1,265 @@
+// Barnes-Hut quadtree over a set of 2D points; each cell keeps the total mass
+// and centre of mass of the points beneath it so distant groups can be
+// treated as a single body. Buffers are reused between rebuilds, so a step
+// allocates nothing once the point count is stable.
+
+// Stop subdividing past this depth so coincident points cannot recurse forever
+const MAX_DEPTH: usize = 24;
+const NO_CHILD: usize = usize::MAX;
+// Cells awaiting a visit: at most three siblings per depth plus the four
+// children just pushed, so a fixed array on the call stack always suffices
+const VISIT_STACK: usize = 4 * MAX_DEPTH;
+
+struct Cell {
+    // Centre and half of the side length of the cell's square bounds
+    cx: f32,
+    cy: f32,
+    half: f32,
+    mass: f32,
+    com_x: f32,
+    com_y: f32,
+    // Range of `order` holding the points inside this cell
+    start: usize,
+    end: usize,
+    children: [usize; 4],
+}
+
+#[derive(Default)]
+pub struct QuadTree {
+    cells: Vec<Cell>,
+    order: Vec<usize>,
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    masses: Vec<f32>,
+}
+
+impl QuadTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Rebuild over points of unit mass
+    pub fn rebuild(&mut self, xs: &[f32], ys: &[f32]) {
+        self.masses.clear();
+        self.masses.resize(xs.len(), 1.0);
+        self.build_root(xs, ys);
+    }
+
+    pub fn rebuild_with_masses(&mut self, xs: &[f32], ys: &[f32], masses: &[f32]) {
+        self.masses.clear();
+        self.masses.extend_from_slice(masses);
+        self.build_root(xs, ys);
+    }
+
+    fn build_root(&mut self, xs: &[f32], ys: &[f32]) {
+        let n = xs.len();
+        self.cells.clear();
+        self.order.clear();
+        self.order.extend(0..n);
+        self.xs.clear();
+        self.xs.extend_from_slice(xs);
+        self.ys.clear();
+        self.ys.extend_from_slice(ys);
+        if n == 0 {
+            return;
+        }
+
+        // Square root cell covering every point
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for i in 0..n {
+            min_x = min_x.min(xs[i]);
+            min_y = min_y.min(ys[i]);
+            max_x = max_x.max(xs[i]);
+            max_y = max_y.max(ys[i]);
+        }
+        let half = ((max_x - min_x).max(max_y - min_y) * 0.5).max(1.0) + 1.0;
+        let cx = (min_x + max_x) * 0.5;
+        let cy = (min_y + max_y) * 0.5;
+
+        let mut order = std::mem::take(&mut self.order);
+        self.build(xs, ys, &mut order, 0, cx, cy, half, 0);
+        self.order = order;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        &mut self,
+        xs: &[f32],
+        ys: &[f32],
+        order: &mut [usize],
+        offset: usize,
+        cx: f32,
+        cy: f32,
+        half: f32,
+        depth: usize,
+    ) -> usize {
+        let mut mass = 0.0f32;
+        let mut sx = 0.0f32;
+        let mut sy = 0.0f32;
+        for &i in order.iter() {
+            let m = self.masses[i];
+            mass += m;
+            sx += xs[i] * m;
+            sy += ys[i] * m;
+        }
+        let (com_x, com_y) = if mass > 0.0 {
+            (sx / mass, sy / mass)
+        } else {
+            (cx, cy)
+        };
+
+        let index = self.cells.len();
+        self.cells.push(Cell {
+            cx,
+            cy,
+            half,
+            mass,
+            com_x,
+            com_y,
+            start: offset,
+            end: offset + order.len(),
+            children: [NO_CHILD; 4],
+        });
+        if order.len() <= 1 || depth >= MAX_DEPTH {
+            return index;
+        }
+
+        // Group points by quadrant: bit 0 is east, bit 1 is south
+        let quadrant = |i: usize| (xs[i] >= cx) as usize | (((ys[i] >= cy) as usize) << 1);
+        order.sort_unstable_by_key(|&i| quadrant(i));
+
+        let quarter = half * 0.5;
+        let mut children = [NO_CHILD; 4];
+        let mut begin = 0;
+        for (q, child) in children.iter_mut().enumerate() {
+            let mut end = begin;
+            while end < order.len() && quadrant(order[end]) == q {
+                end += 1;
+            }
+            if end > begin {
+                let ccx = if q & 1 == 1 {
+                    cx + quarter
+                } else {
+                    cx - quarter
+                };
+                let ccy = if q & 2 == 2 {
+                    cy + quarter
+                } else {
+                    cy - quarter
+                };
+                *child = self.build(
+                    xs,
+                    ys,
+                    &mut order[begin..end],
+                    offset + begin,
+                    ccx,
+                    ccy,
+                    quarter,
+                    depth + 1,
+                );
+            }
+            begin = end;
+        }
+        self.cells[index].children = children;
+        index
+    }
+
+    // Visit every body (single point or summarised cell) acting on point `i`
+    // at (x, y), calling `f(dx, dy, mass)` with the offset from the point to
+    // the body. Cells whose width / distance falls below `theta` are
+    // summarised by their centre of mass.
+    pub fn visit<F>(&self, i: usize, x: f32, y: f32, theta: f32, mut f: F)
+    where
+        F: FnMut(f32, f32, f32),
+    {
+        if self.cells.is_empty() {
+            return;
+        }
+        let theta2 = theta * theta;
+        let mut stack = [0usize; VISIT_STACK];
+        let mut pending = 1;
+        while pending > 0 {
+            pending -= 1;
+            let cell = &self.cells[stack[pending]];
+            let dx = cell.com_x - x;
+            let dy = cell.com_y - y;
+            let d2 = dx * dx + dy * dy;
+            let width = cell.half * 2.0;
+            let contains_self =
+                (x - cell.cx).abs() <= cell.half && (y - cell.cy).abs() <= cell.half;
+
+            if !contains_self && width * width < theta2 * d2 {
+                f(dx, dy, cell.mass);
+                continue;
+            }
+
+            if cell.children == [NO_CHILD; 4] {
+                for &j in &self.order[cell.start..cell.end] {
+                    if j != i {
+                        f(self.xs[j] - x, self.ys[j] - y, self.masses[j]);
+                    }
+                }
+                continue;
+            }
+
+            for &child in &cell.children {
+                if child != NO_CHILD {
+                    stack[pending] = child;
+                    pending += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scatter(n: usize, seed: u64) -> (Vec<f32>, Vec<f32>) {
+        let mut seed = seed;
+        (0..n)
+            .map(|_| {
+                let x = crate::generators::utils::rand_f32(&mut seed) * 600.0 - 300.0;
+                let y = crate::generators::utils::rand_f32(&mut seed) * 600.0 - 300.0;
+                (x, y)
+            })
+            .unzip()
+    }
+
+    // With theta 0 nothing is summarised, so every other point is visited
+    // exactly once; a positive theta still accounts for all their mass
+    fn assert_matches_brute_force(tree: &QuadTree, xs: &[f32], ys: &[f32]) {
+        for i in 0..xs.len() {
+            let mut offsets = Vec::new();
+            tree.visit(i, xs[i], ys[i], 0.0, |dx, dy, mass| {
+                assert_eq!(mass, 1.0);
+                offsets.push((dx, dy));
+            });
+            let mut expected: Vec<(f32, f32)> = (0..xs.len())
+                .filter(|&j| j != i)
+                .map(|j| (xs[j] - xs[i], ys[j] - ys[i]))
+                .collect();
+            offsets.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            expected.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+            assert_eq!(offsets, expected, "point {i}");
+
+            let mut mass = 0.0;
+            tree.visit(i, xs[i], ys[i], 1.0, |_, _, m| mass += m);
+            assert!((mass - (xs.len() - 1) as f32).abs() < 1e-3, "point {i}");
+        }
+    }
+
+    #[test]
+    fn rebuilds_match_brute_force() {
+        let mut tree = QuadTree::new();
+        let (xs, ys) = scatter(300, 1);
+        tree.rebuild(&xs, &ys);
+        assert_matches_brute_force(&tree, &xs, &ys);
+
+        let (xs, ys) = scatter(120, 2);
+        tree.rebuild(&xs, &ys);
+        assert_matches_brute_force(&tree, &xs, &ys);
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::edge::GpugEdge;
+use crate::simulation::config::ForceConfig;
+use crate::simulation::constraints::Constraint;
+use crate::simulation::force_simulation::ForceSimulation;
+use crate::simulation::forces::Force;
+
+// Positions published by the simulation thread for the render path to swap in
+#[derive(Clone, Debug)]
+pub struct PositionSnapshot {
+    pub positions: Vec<(f32, f32)>,
+    // Total steps taken when the snapshot was published
+    pub tick: u64,
+    pub converged: bool,
+    // Count of position resets applied before the step, see `set_positions`
+    epoch: u64,
+}
+
+// Edits made from the UI thread, applied by the simulation thread before its next step
+#[derive(Default)]
+struct Pending {
+    // Replaces every position before `moves` are applied
+    positions: Option<Vec<(f32, f32)>>,
+    moves: Vec<(usize, f32, f32)>,
+    pinned: Option<Vec<bool>>,
+    // Single-node pin changes made after the last queued mask
+    pins: Vec<(usize, bool)>,
+    radii: Option<Vec<f32>>,
+    edges: Option<Vec<GpugEdge>>,
+    constraints: Option<Vec<Constraint>>,
+    config: Option<ForceConfig>,
+    center: Option<(f32, f32)>,
+    forces: Vec<ForceEdit>,
+    alpha: f32,
+}
+
+// Force registrations in the order they were queued
+enum ForceEdit {
+    Add(Box<dyn Force>),
+    Remove(String),
+}
+
+// A `ForceSimulation` that can be stepped on a background thread while the
+// UI reads finished frames from a separate front buffer, so rendering never
+// waits on a physics step
+#[derive(Clone)]
+pub struct SharedSimulation {
+    simulation: Arc<Mutex<ForceSimulation>>,
+    front: Arc<Mutex<Option<PositionSnapshot>>>,
+    pending: Arc<Mutex<Pending>>,
+    tick: Arc<AtomicU64>,
+    // Bumped by every queued position reset; frames stepped from older
+    // positions are dropped instead of handed to the UI
+    epoch: Arc<AtomicU64>,
+}
+
+impl SharedSimulation {
+    pub fn new(simulation: ForceSimulation) -> Self {
+        Self {
+            simulation: Arc::new(Mutex::new(simulation)),
+            front: Arc::new(Mutex::new(None)),
+            pending: Arc::new(Mutex::new(Pending::default())),
+            tick: Arc::new(AtomicU64::new(0)),
+            epoch: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // Direct access to the simulation, e.g. for headless use; blocks while a
+    // step runs, so the UI queues its edits instead
+    pub fn lock(&self) -> MutexGuard<'_, ForceSimulation> {
+        lock(&self.simulation)
+    }
+
+    // Queue replacing every position, e.g. after a layout. Frames already
+    // stepped from the old positions are never returned by `take_snapshot`.
+    pub fn set_positions(&self, positions: Vec<(f32, f32)>) {
+        let mut pending = lock(&self.pending);
+        pending.positions = Some(positions);
+        pending.moves.clear();
+        self.epoch.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Queue a node move without waiting for the simulation thread
+    pub fn push_position(&self, index: usize, x: f32, y: f32) {
+        lock(&self.pending).moves.push((index, x, y));
+    }
+
+    // Queue a replacement of every node's pinned flag
+    pub fn set_pinned_mask(&self, pinned: Vec<bool>) {
+        let mut pending = lock(&self.pending);
+        pending.pinned = Some(pinned);
+        pending.pins.clear();
+    }
+
+    // Queue pinning or releasing a single node
+    pub fn set_pinned(&self, index: usize, pinned: bool) {
+        let mut pending = lock(&self.pending);
+        match &mut pending.pinned {
+            Some(mask) if index < mask.len() => mask[index] = pinned,
+            _ => pending.pins.push((index, pinned)),
+        }
+    }
+
+    // Queue new node radii
+    pub fn set_radii(&self, radii: Vec<f32>) {
+        lock(&self.pending).radii = Some(radii);
+    }
+
+    // Queue replacing the edges
+    pub fn set_edges(&self, edges: Vec<GpugEdge>) {
+        lock(&self.pending).edges = Some(edges);
+    }
+
+    // Queue replacing the layout constraints
+    pub fn set_constraints(&self, constraints: Vec<Constraint>) {
+        lock(&self.pending).constraints = Some(constraints);
+    }
+
+    // Queue new physics parameters
+    pub fn set_config(&self, config: ForceConfig) {
+        lock(&self.pending).config = Some(config);
+    }
+
+    // Queue registering a force after the existing ones
+    pub fn add_force(&self, force: Box<dyn Force>) {
+        lock(&self.pending).forces.push(ForceEdit::Add(force));
+    }
+
+    // Queue unregistering the first force called `name`; the force is dropped
+    // on the simulation thread
+    pub fn remove_force(&self, name: &str) {
+        lock(&self.pending)
+            .forces
+            .push(ForceEdit::Remove(name.to_owned()));
+    }
+
+    // Queue a new gravity centre, e.g. when the viewport moves
+    pub fn set_center(&self, x: f32, y: f32) {
+        lock(&self.pending).center = Some((x, y));
+    }
+
+    // Queue a reheat to at least `alpha` without waiting for the simulation thread
+    pub fn reheat_to(&self, alpha: f32) {
+        let mut pending = lock(&self.pending);
+        pending.alpha = pending.alpha.max(alpha);
+    }
+
+    // Apply queued edits, advance one step and publish the result; returns
+    // true once the layout has converged. Meant for the simulation thread.
+    pub fn tick(&self) -> bool {
+        // Read the epoch with the edits so it counts exactly the resets applied
+        let (edits, epoch) = {
+            let mut pending = lock(&self.pending);
+            let epoch = self.epoch.load(Ordering::Relaxed);
+            (std::mem::take(&mut *pending), epoch)
+        };
+        let Pending {
+            positions,
+            moves,
+            pinned,
+            pins,
+            radii,
+            edges,
+            constraints,
+            config,
+            center,
+            forces,
+            alpha,
+        } = edits;
+        let mut simulation = self.lock();
+        if let Some(positions) = positions {
+            simulation.set_positions(&positions);
+        }
+        if let Some(edges) = edges {
+            simulation.set_edges(edges);
+        }
+        if let Some(radii) = radii {
+            simulation.set_radii(&radii);
+        }
+        if let Some(constraints) = constraints {
+            simulation.set_constraints(constraints);
+        }
+        if let Some(pinned) = pinned {
+            simulation.set_pinned_mask(&pinned);
+        }
+        for (index, pinned) in pins {
+            simulation.set_pinned(index, pinned);
+        }
+        for (index, x, y) in moves {
+            simulation.set_position(index, x, y);
+        }
+        if let Some(config) = config {
+            simulation.set_config(config);
+        }
+        if let Some((x, y)) = center {
+            simulation.set_center(x, y);
+        }
+        for edit in forces {
+            match edit {
+                ForceEdit::Add(force) => simulation.add_force(force),
+                ForceEdit::Remove(name) => {
+                    simulation.remove_force(&name);
+                }
+            }
+        }
+        if alpha > 0.0 {
+            simulation.reheat_to(alpha);
+        }
+        simulation.step();
+        let converged = simulation.is_converged();
+
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        let snapshot = PositionSnapshot {
+            positions: simulation.positions().collect(),
+            tick,
+            converged,
+            epoch,
+        };
+        drop(simulation);
+        *lock(&self.front) = Some(snapshot);
+        converged
+    }
+
+    // Take the latest published snapshot, if a new one arrived since the last
+    // call and was stepped from the most recently queued positions
+    pub fn take_snapshot(&self) -> Option<PositionSnapshot> {
+        let snapshot = lock(&self.front).take()?;
+        (snapshot.epoch == self.epoch.load(Ordering::Relaxed)).then_some(snapshot)
+    }
+}
+
+// A panic on the simulation thread must not take the UI down with it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_from_replaced_positions_are_dropped() {
+        let simulation = ForceSimulation::new(vec![(0.0, 0.0), (50.0, 0.0)], Vec::new());
+        let shared = SharedSimulation::new(simulation);
+        shared.tick();
+        shared.set_positions(vec![(500.0, 500.0), (550.0, 500.0)]);
+        assert!(shared.take_snapshot().is_none());
+
+        shared.tick();
+        let snapshot = shared
+            .take_snapshot()
+            .expect("frame from the new positions");
+        assert!(snapshot.positions[0].0 > 400.0);
+    }
+}
@@ -0,0 +1,161 @@
+// Uniform grid over node positions stored as compressed rows: nodes are
+// counting-sorted by a hash of their cell so each bucket is one contiguous
+// slice. Buffers are reused between rebuilds, so a step allocates nothing
+// once the node count is stable.
+#[derive(Default)]
+pub struct SpatialGrid {
+    cell: f32,
+    // Bucket count minus one; the bucket count is a power of two
+    mask: usize,
+    // Bucket b holds entries[starts[b]..starts[b + 1]]
+    starts: Vec<u32>,
+    entries: Vec<u32>,
+    // Point and cell of each entry, stored in bucket order so scanning a
+    // bucket reads contiguous memory; the cell skips others sharing a bucket
+    points: Vec<(f32, f32)>,
+    entry_cells: Vec<(i32, i32)>,
+    // Cell of every node by index
+    cells: Vec<(i32, i32)>,
+}
+
+impl SpatialGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell
+    }
+
+    // Re-bin `count` nodes whose points are given by `point`
+    pub fn rebuild(&mut self, count: usize, cell: f32, point: impl Fn(usize) -> (f32, f32)) {
+        self.cell = cell.max(1e-3);
+        let buckets = (count * 2).max(16).next_power_of_two();
+        self.mask = buckets - 1;
+
+        let size = self.cell;
+        self.cells.clear();
+        self.cells.extend((0..count).map(|i| {
+            let (x, y) = point(i);
+            ((x / size).floor() as i32, (y / size).floor() as i32)
+        }));
+
+        self.starts.clear();
+        self.starts.resize(buckets + 1, 0);
+        for &cell in &self.cells {
+            self.starts[bucket(cell, self.mask) + 1] += 1;
+        }
+        for b in 0..buckets {
+            self.starts[b + 1] += self.starts[b];
+        }
+        // Fill each bucket using `starts[b]` as its write cursor, then shift
+        // the cursors back into bucket starts
+        self.entries.clear();
+        self.entries.resize(count, 0);
+        self.points.clear();
+        self.points.resize(count, (0.0, 0.0));
+        self.entry_cells.clear();
+        self.entry_cells.resize(count, (0, 0));
+        for (i, &cell) in self.cells.iter().enumerate() {
+            let b = bucket(cell, self.mask);
+            let slot = self.starts[b] as usize;
+            self.entries[slot] = i as u32;
+            self.points[slot] = point(i);
+            self.entry_cells[slot] = cell;
+            self.starts[b] += 1;
+        }
+        for b in (1..=buckets).rev() {
+            self.starts[b] = self.starts[b - 1];
+        }
+        self.starts[0] = 0;
+    }
+
+    pub fn cell_of(&self, x: f32, y: f32) -> (i32, i32) {
+        (
+            (x / self.cell).floor() as i32,
+            (y / self.cell).floor() as i32,
+        )
+    }
+
+    // Node indices grouped by cell; visiting nodes in this order keeps
+    // neighbouring cells hot in cache
+    pub fn order(&self) -> &[u32] {
+        &self.entries
+    }
+
+    // Call `f(j, x, y)` with every node j in the 3x3 block of cells around
+    // node `i`, including `i` itself, and its point as of the last rebuild
+    pub fn for_each_near(&self, i: usize, mut f: impl FnMut(usize, f32, f32)) {
+        let (gx, gy) = self.cells[i];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                self.for_each_in_cell((gx + dx, gy + dy), &mut f);
+            }
+        }
+    }
+
+    // Call `f(j, x, y)` with every node j binned into `cell`
+    pub fn for_each_in_cell(&self, cell: (i32, i32), mut f: impl FnMut(usize, f32, f32)) {
+        if self.starts.is_empty() {
+            return;
+        }
+        let b = bucket(cell, self.mask);
+        for slot in self.starts[b] as usize..self.starts[b + 1] as usize {
+            if self.entry_cells[slot] == cell {
+                let (x, y) = self.points[slot];
+                f(self.entries[slot] as usize, x, y);
+            }
+        }
+    }
+}
+
+fn bucket((gx, gy): (i32, i32), mask: usize) -> usize {
+    let h = (gx as u32).wrapping_mul(0x9E37_79B1) ^ (gy as u32).wrapping_mul(0x85EB_CA77);
+    (h ^ (h >> 15)) as usize & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scatter(n: usize, seed: u64) -> Vec<(f32, f32)> {
+        let mut seed = seed;
+        (0..n)
+            .map(|_| {
+                let x = crate::generators::utils::rand_f32(&mut seed) * 600.0 - 300.0;
+                let y = crate::generators::utils::rand_f32(&mut seed) * 600.0 - 300.0;
+                (x, y)
+            })
+            .collect()
+    }
+
+    fn assert_matches_brute_force(grid: &SpatialGrid, points: &[(f32, f32)]) {
+        let cells: Vec<(i32, i32)> = points.iter().map(|&(x, y)| grid.cell_of(x, y)).collect();
+        for i in 0..points.len() {
+            let mut near = Vec::new();
+            grid.for_each_near(i, |j, x, y| {
+                assert_eq!((x, y), points[j]);
+                near.push(j);
+            });
+            near.sort_unstable();
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&j| {
+                    (cells[i].0 - cells[j].0).abs() <= 1 && (cells[i].1 - cells[j].1).abs() <= 1
+                })
+                .collect();
+            assert_eq!(near, expected, "node {i}");
+        }
+    }
+
+    #[test]
+    fn rebuilds_match_brute_force() {
+        let mut grid = SpatialGrid::new();
+        let first = scatter(400, 1);
+        grid.rebuild(first.len(), 40.0, |i| first[i]);
+        assert_matches_brute_force(&grid, &first);
+
+        let second = scatter(150, 2);
+        grid.rebuild(second.len(), 25.0, |i| second[i]);
+        assert_matches_brute_force(&grid, &second);
+    }
+}